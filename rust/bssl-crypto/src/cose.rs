@@ -0,0 +1,445 @@
+/* Copyright (c) 2024, Google Inc.
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+ * SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+ * OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+ * CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ */
+
+//! COSE_Sign1 (RFC 9052 section 4.2) signing and verification over ES256 (ECDSA P-256 / SHA-256,
+//! [`crate::ec_signer`]), plus a minimal certificate-chain validator built on top of it.
+//!
+//! This only speaks the fixed-shape subset of CBOR that COSE_Sign1 needs (unsigned/negative
+//! integers, byte strings, and small arrays/maps used as headers) rather than pulling in a
+//! general-purpose CBOR decoder, since that's all a single well-known message layout requires.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::ec_signer::{EcPrivateKey, EcPublicKey, EcSignerError};
+
+/// COSE algorithm identifier for ECDSA with SHA-256, as registered in the COSE algorithms
+/// registry (RFC 9053 section 2.1).
+const ALG_ES256: i64 = -7;
+
+/// COSE common header parameter label for the algorithm (RFC 9052 section 3.1).
+const LABEL_ALG: u64 = 1;
+
+/// `COSE_Key` map labels used by [`encode_cose_key`]/[`decode_cose_key`] (RFC 9053 section 7.1).
+const KEY_LABEL_KTY: i64 = 1;
+const KEY_LABEL_ALG: i64 = 3;
+const KEY_LABEL_CRV: i64 = -1;
+const KEY_LABEL_X: i64 = -2;
+const KEY_LABEL_Y: i64 = -3;
+
+/// `COSE_Key` key type value for double-coordinate EC keys (RFC 9053 section 7.1).
+const KTY_EC2: i64 = 2;
+
+/// `COSE_Key` curve identifier for P-256 (RFC 9053 section 7.1.1).
+const CRV_P256: i64 = 1;
+
+/// Error returned from unsuccessful COSE operations, covering both malformed CBOR/COSE input and
+/// signature verification failures.
+#[derive(Debug)]
+pub struct CoseError;
+
+impl From<EcSignerError> for CoseError {
+    fn from(_: EcSignerError) -> Self {
+        CoseError
+    }
+}
+
+// --- Minimal CBOR encoding -------------------------------------------------
+
+fn encode_head(out: &mut Vec<u8>, major: u8, value: u64) {
+    let major = major << 5;
+    match value {
+        0..=23 => out.push(major | value as u8),
+        24..=0xff => {
+            out.push(major | 24);
+            out.push(value as u8);
+        }
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend_from_slice(&(value as u16).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 26);
+            out.extend_from_slice(&(value as u32).to_be_bytes());
+        }
+    }
+}
+
+fn encode_bstr(out: &mut Vec<u8>, bytes: &[u8]) {
+    encode_head(out, 2, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+fn encode_int(out: &mut Vec<u8>, value: i64) {
+    if value >= 0 {
+        encode_head(out, 0, value as u64);
+    } else {
+        encode_head(out, 1, (-1 - value) as u64);
+    }
+}
+
+/// Encodes the protected header `{1: ALG_ES256}`, the only header this module produces.
+fn encode_protected_header() -> Vec<u8> {
+    let mut out = Vec::new();
+    encode_head(&mut out, 5, 1);
+    encode_int(&mut out, LABEL_ALG as i64);
+    encode_int(&mut out, ALG_ES256);
+    out
+}
+
+/// Encodes the RFC 9052 section 4.4 `Sig_structure` that is actually signed/verified.
+fn encode_sig_structure(protected: &[u8], external_aad: &[u8], payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    encode_head(&mut out, 4, 4);
+    encode_bstr(&mut out, b"Signature1");
+    encode_bstr(&mut out, protected);
+    encode_bstr(&mut out, external_aad);
+    encode_bstr(&mut out, payload);
+    out
+}
+
+/// Encodes `public_key` (uncompressed SEC1 `0x04 || X || Y`, 65 bytes) as a `COSE_Key` map
+/// (RFC 9053 section 7.1): `{1: 2, 3: alg, -1: 1, -2: X, -3: Y}`, i.e. an EC2 P-256 key under
+/// `alg`.
+fn encode_cose_key(public_key: &[u8], alg: i64) -> Vec<u8> {
+    debug_assert_eq!(public_key.len(), 65);
+    let mut out = Vec::new();
+    encode_head(&mut out, 5, 5);
+    encode_int(&mut out, KEY_LABEL_KTY);
+    encode_int(&mut out, KTY_EC2);
+    encode_int(&mut out, KEY_LABEL_ALG);
+    encode_int(&mut out, alg);
+    encode_int(&mut out, KEY_LABEL_CRV);
+    encode_int(&mut out, CRV_P256);
+    encode_int(&mut out, KEY_LABEL_X);
+    encode_bstr(&mut out, &public_key[1..33]);
+    encode_int(&mut out, KEY_LABEL_Y);
+    encode_bstr(&mut out, &public_key[33..65]);
+    out
+}
+
+// --- Minimal CBOR decoding --------------------------------------------------
+
+/// Reads one head byte (and any following length/value bytes), returning `(major, value)`.
+fn read_head(input: &[u8], pos: &mut usize) -> Result<(u8, u64), CoseError> {
+    let first = *input.get(*pos).ok_or(CoseError)?;
+    *pos += 1;
+    let major = first >> 5;
+    let info = first & 0x1f;
+    let value = match info {
+        0..=23 => info as u64,
+        24 => {
+            let byte = *input.get(*pos).ok_or(CoseError)?;
+            *pos += 1;
+            byte as u64
+        }
+        25 => {
+            let bytes: [u8; 2] = input.get(*pos..*pos + 2).ok_or(CoseError)?.try_into().map_err(|_| CoseError)?;
+            *pos += 2;
+            u16::from_be_bytes(bytes) as u64
+        }
+        26 => {
+            let bytes: [u8; 4] = input.get(*pos..*pos + 4).ok_or(CoseError)?.try_into().map_err(|_| CoseError)?;
+            *pos += 4;
+            u32::from_be_bytes(bytes) as u64
+        }
+        _ => return Err(CoseError),
+    };
+    Ok((major, value))
+}
+
+fn read_bstr<'a>(input: &'a [u8], pos: &mut usize) -> Result<&'a [u8], CoseError> {
+    let (major, len) = read_head(input, pos)?;
+    if major != 2 {
+        return Err(CoseError);
+    }
+    let len = len as usize;
+    let bytes = input.get(*pos..*pos + len).ok_or(CoseError)?;
+    *pos += len;
+    Ok(bytes)
+}
+
+/// Reads one signed integer (CBOR major type 0 or 1), as used for `COSE_Key` labels and values.
+fn read_int(input: &[u8], pos: &mut usize) -> Result<i64, CoseError> {
+    let (major, value) = read_head(input, pos)?;
+    match major {
+        0 => Ok(value as i64),
+        1 => Ok(-1 - value as i64),
+        _ => Err(CoseError),
+    }
+}
+
+/// Decodes a `COSE_Key` map produced by [`encode_cose_key`], returning its declared algorithm
+/// and subject public key in uncompressed SEC1 encoding. Only the fixed `{1, 3, -1, -2, -3}` EC2
+/// P-256 shape `encode_cose_key` emits is accepted.
+fn decode_cose_key(input: &[u8]) -> Result<(i64, Vec<u8>), CoseError> {
+    let mut pos = 0;
+    let (major, count) = read_head(input, &mut pos)?;
+    if major != 5 || count != 5 {
+        return Err(CoseError);
+    }
+    if read_int(input, &mut pos)? != KEY_LABEL_KTY || read_int(input, &mut pos)? != KTY_EC2 {
+        return Err(CoseError);
+    }
+    if read_int(input, &mut pos)? != KEY_LABEL_ALG {
+        return Err(CoseError);
+    }
+    let alg = read_int(input, &mut pos)?;
+    if read_int(input, &mut pos)? != KEY_LABEL_CRV || read_int(input, &mut pos)? != CRV_P256 {
+        return Err(CoseError);
+    }
+    if read_int(input, &mut pos)? != KEY_LABEL_X {
+        return Err(CoseError);
+    }
+    let x = read_bstr(input, &mut pos)?;
+    if read_int(input, &mut pos)? != KEY_LABEL_Y {
+        return Err(CoseError);
+    }
+    let y = read_bstr(input, &mut pos)?;
+
+    let mut public_key = vec![0x04u8];
+    public_key.extend_from_slice(x);
+    public_key.extend_from_slice(y);
+    Ok((alg, public_key))
+}
+
+/// Skips over one CBOR data item, used to step over the (currently always-empty) unprotected
+/// header map without needing a general-purpose decoder.
+fn skip_item(input: &[u8], pos: &mut usize) -> Result<(), CoseError> {
+    let (major, value) = read_head(input, pos)?;
+    match major {
+        0 | 1 => Ok(()),
+        2 | 3 => {
+            let len = value as usize;
+            *pos = pos.checked_add(len).ok_or(CoseError)?;
+            if *pos > input.len() {
+                return Err(CoseError);
+            }
+            Ok(())
+        }
+        4 => {
+            for _ in 0..value {
+                skip_item(input, pos)?;
+            }
+            Ok(())
+        }
+        5 => {
+            for _ in 0..value * 2 {
+                skip_item(input, pos)?;
+            }
+            Ok(())
+        }
+        _ => Err(CoseError),
+    }
+}
+
+/// Parses the 4-element `[protected, unprotected, payload, signature]` array, returning the
+/// protected header bytes, payload bytes, and signature bytes.
+fn parse_sign1(input: &[u8]) -> Result<(&[u8], &[u8], &[u8]), CoseError> {
+    let mut pos = 0;
+    let (major, count) = read_head(input, &mut pos)?;
+    if major != 4 || count != 4 {
+        return Err(CoseError);
+    }
+    let protected = read_bstr(input, &mut pos)?;
+    skip_item(input, &mut pos)?; // unprotected header map
+    let payload = read_bstr(input, &mut pos)?;
+    let signature = read_bstr(input, &mut pos)?;
+    Ok((protected, payload, signature))
+}
+
+/// Signs `payload` as a COSE_Sign1 message with `external_aad` bound into the signature but not
+/// carried in the output, per RFC 9052 section 4.3.
+pub fn sign(key: &EcPrivateKey, payload: &[u8], external_aad: &[u8]) -> Result<Vec<u8>, CoseError> {
+    let protected = encode_protected_header();
+    let sig_structure = encode_sig_structure(&protected, external_aad, payload);
+    let signature = key.sign(&sig_structure)?;
+
+    let mut out = Vec::new();
+    encode_head(&mut out, 4, 4);
+    encode_bstr(&mut out, &protected);
+    encode_head(&mut out, 5, 0); // empty unprotected header map
+    encode_bstr(&mut out, payload);
+    encode_bstr(&mut out, &signature);
+    Ok(out)
+}
+
+/// Verifies a COSE_Sign1 message produced by [`sign`] and returns its payload.
+pub fn verify(key: &EcPublicKey, cose_sign1: &[u8], external_aad: &[u8]) -> Result<Vec<u8>, CoseError> {
+    let (protected, payload, signature) = parse_sign1(cose_sign1)?;
+    if protected != encode_protected_header() {
+        return Err(CoseError);
+    }
+    let sig_structure = encode_sig_structure(protected, external_aad, payload);
+    key.verify(&sig_structure, signature)?;
+    Ok(payload.to_vec())
+}
+
+/// One link in a certificate chain: a COSE_Sign1 message whose payload is a `COSE_Key` encoding
+/// the subject's public key, signed by the issuer (the previous certificate in the chain, or the
+/// subject itself for the self-signed root).
+pub struct Certificate {
+    /// A COSE_Sign1 message whose payload is a `COSE_Key` (see [`encode_cose_key`]), signed by
+    /// the issuer.
+    pub cose_sign1: Vec<u8>,
+}
+
+/// Builds the `COSE_Sign1` payload for a [`Certificate`] certifying `subject_public_key`.
+pub fn encode_subject_public_key(subject_public_key: &[u8]) -> Vec<u8> {
+    encode_cose_key(subject_public_key, ALG_ES256)
+}
+
+/// Extracts the `COSE_Key`-encoded payload embedded in `cose_sign1` without verifying its
+/// signature, returning its declared algorithm and subject public key.
+fn embedded_key(cose_sign1: &[u8]) -> Result<(i64, Vec<u8>), CoseError> {
+    let (_protected, payload, _signature) = parse_sign1(cose_sign1)?;
+    decode_cose_key(payload)
+}
+
+/// Validates a certificate chain, DICE/Open-DICE-style: `chain[0]` is self-signed and seeds the
+/// chain with its own embedded `COSE_Key`, then each subsequent entry is verified with the
+/// public key embedded in the previous entry and becomes the key used to verify the next. Each
+/// entry's embedded key algorithm must be ES256, matching the signature algorithm this module
+/// speaks. No certificate extensions, validity periods, or names are interpreted; this only
+/// establishes the signature chain of custody down to the leaf. Rejects an empty `chain`.
+pub fn validate_chain(chain: &[Certificate]) -> Result<(), CoseError> {
+    let (first, rest) = chain.split_first().ok_or(CoseError)?;
+
+    let (alg, root_key_bytes) = embedded_key(&first.cose_sign1)?;
+    if alg != ALG_ES256 {
+        return Err(CoseError);
+    }
+    let root_key = EcPublicKey::from_bytes(&root_key_bytes)?;
+    verify(&root_key, &first.cose_sign1, &[])?;
+
+    let mut issuer_key = root_key;
+    for cert in rest {
+        let payload = verify(&issuer_key, &cert.cose_sign1, &[])?;
+        let (alg, subject_key_bytes) = decode_cose_key(&payload)?;
+        if alg != ALG_ES256 {
+            return Err(CoseError);
+        }
+        issuer_key = EcPublicKey::from_bytes(&subject_key_bytes)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_then_verify_round_trips() {
+        let key = EcPrivateKey::generate().expect("key generation should succeed");
+        let public_key = key.public_key().expect("public key derivation should succeed");
+        let cose_sign1 = sign(&key, b"payload", b"aad").expect("signing should succeed");
+        let payload = verify(&public_key, &cose_sign1, b"aad").expect("verification should succeed");
+        assert_eq!(payload, b"payload");
+    }
+
+    #[test]
+    fn verify_rejects_wrong_aad() {
+        let key = EcPrivateKey::generate().expect("key generation should succeed");
+        let public_key = key.public_key().expect("public key derivation should succeed");
+        let cose_sign1 = sign(&key, b"payload", b"aad").expect("signing should succeed");
+        assert!(verify(&public_key, &cose_sign1, b"other aad").is_err());
+    }
+
+    #[test]
+    fn verify_rejects_wrong_key() {
+        let key = EcPrivateKey::generate().expect("key generation should succeed");
+        let other_key = EcPrivateKey::generate().expect("key generation should succeed");
+        let other_public_key = other_key.public_key().expect("public key derivation should succeed");
+        let cose_sign1 = sign(&key, b"payload", b"aad").expect("signing should succeed");
+        assert!(verify(&other_public_key, &cose_sign1, b"aad").is_err());
+    }
+
+    /// Builds a self-signed root [`Certificate`] over `root`'s own public key.
+    fn root_cert(root: &EcPrivateKey) -> Certificate {
+        let root_public_bytes = root
+            .public_key()
+            .expect("public key derivation should succeed")
+            .to_bytes();
+        Certificate {
+            cose_sign1: sign(root, &encode_subject_public_key(&root_public_bytes), &[])
+                .expect("signing should succeed"),
+        }
+    }
+
+    /// Builds a [`Certificate`] certifying `subject`'s public key, issued by `issuer`.
+    fn issued_cert(issuer: &EcPrivateKey, subject: &EcPrivateKey) -> Certificate {
+        let subject_public_bytes = subject
+            .public_key()
+            .expect("public key derivation should succeed")
+            .to_bytes();
+        Certificate {
+            cose_sign1: sign(issuer, &encode_subject_public_key(&subject_public_bytes), &[])
+                .expect("signing should succeed"),
+        }
+    }
+
+    #[test]
+    fn validate_chain_accepts_valid_chain() {
+        let root = EcPrivateKey::generate().expect("key generation should succeed");
+        let leaf = EcPrivateKey::generate().expect("key generation should succeed");
+
+        let chain = [root_cert(&root), issued_cert(&root, &leaf)];
+        validate_chain(&chain).expect("chain should validate");
+    }
+
+    #[test]
+    fn validate_chain_rejects_broken_link() {
+        let root = EcPrivateKey::generate().expect("key generation should succeed");
+        let imposter = EcPrivateKey::generate().expect("key generation should succeed");
+        let leaf = EcPrivateKey::generate().expect("key generation should succeed");
+
+        let chain = [root_cert(&root), issued_cert(&imposter, &leaf)];
+        assert!(validate_chain(&chain).is_err());
+    }
+
+    #[test]
+    fn validate_chain_rejects_empty_chain() {
+        assert!(validate_chain(&[]).is_err());
+    }
+
+    #[test]
+    fn validate_chain_rejects_non_self_signed_root() {
+        let root = EcPrivateKey::generate().expect("key generation should succeed");
+        let other = EcPrivateKey::generate().expect("key generation should succeed");
+
+        // The root entry is signed by a different key than the one it embeds, so it is not
+        // actually self-signed.
+        let chain = [issued_cert(&other, &root)];
+        assert!(validate_chain(&chain).is_err());
+    }
+
+    #[test]
+    fn validate_chain_rejects_algorithm_mismatch() {
+        let root = EcPrivateKey::generate().expect("key generation should succeed");
+        let root_public_bytes = root
+            .public_key()
+            .expect("public key derivation should succeed")
+            .to_bytes();
+
+        // A `COSE_Key` whose declared algorithm does not match ES256, the only signature
+        // algorithm this module speaks.
+        let mismatched_key = encode_cose_key(&root_public_bytes, ALG_ES256 + 1);
+        let cert = Certificate {
+            cose_sign1: sign(&root, &mismatched_key, &[]).expect("signing should succeed"),
+        };
+
+        assert!(validate_chain(&[cert]).is_err());
+    }
+}
@@ -0,0 +1,902 @@
+/* Copyright (c) 2024, Google Inc.
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+ * SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+ * OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+ * CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ */
+
+//! ECDSA P-256 signing and verification over raw (SEC1) key encodings, used by [`crate::cose`] to
+//! build COSE_Sign1 objects. This module is intentionally narrow: P-256/SHA-256 is the only curve
+//! and hash pairing needed by the COSE layer today.
+//!
+//! [`secp256k1`] additionally covers the Ethereum/Bitcoin-style curve, which none of the MLS
+//! cipher suites use, with Ethereum/Bitcoin-style recoverable signatures.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Length in bytes of an uncompressed SEC1 P-256 public key point (`0x04 || X || Y`).
+pub const PUBLIC_KEY_LEN: usize = 65;
+
+/// Length in bytes of a raw (big-endian, unencoded) P-256 private key scalar.
+pub const PRIVATE_KEY_LEN: usize = 32;
+
+/// Error returned from unsuccessful EC signing operations.
+#[derive(Debug)]
+pub struct EcSignerError;
+
+/// A P-256 private key used to produce ECDSA signatures over SHA-256 digests.
+pub struct EcPrivateKey {
+    key: *mut bssl_sys::EC_KEY,
+}
+
+/// A P-256 public key used to verify ECDSA signatures over SHA-256 digests.
+pub struct EcPublicKey {
+    key: *mut bssl_sys::EC_KEY,
+}
+
+fn new_p256_key() -> Result<*mut bssl_sys::EC_KEY, EcSignerError> {
+    // Safety: `NID_X9_62_prime256v1` is a valid, constant curve identifier.
+    let key = unsafe { bssl_sys::EC_KEY_new_by_curve_name(bssl_sys::NID_X9_62_prime256v1) };
+    if key.is_null() {
+        return Err(EcSignerError);
+    }
+    Ok(key)
+}
+
+impl EcPrivateKey {
+    /// Generates a new random P-256 private key.
+    pub fn generate() -> Result<Self, EcSignerError> {
+        let key = new_p256_key()?;
+        // Safety: `key` was just allocated and checked non-null above.
+        let result = unsafe { bssl_sys::EC_KEY_generate_key(key) };
+        if result != 1 {
+            // Safety: `key` is owned here and not yet exposed to a wrapper that would double-free it.
+            unsafe { bssl_sys::EC_KEY_free(key) };
+            return Err(EcSignerError);
+        }
+        Ok(Self { key })
+    }
+
+    /// Parses a private key from its raw (big-endian) scalar encoding and derives its public
+    /// point, as is needed when loading a key that was serialized via [`Self::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EcSignerError> {
+        if bytes.len() != PRIVATE_KEY_LEN {
+            return Err(EcSignerError);
+        }
+        let key = new_p256_key()?;
+
+        // Safety: `bytes` points to `bytes.len()` readable bytes; `BN_bin2bn` allocates a new
+        // `BIGNUM` holding them and does not retain a reference to `bytes` itself.
+        let scalar = unsafe { bssl_sys::BN_bin2bn(bytes.as_ptr(), bytes.len(), core::ptr::null_mut()) };
+        if scalar.is_null() {
+            // Safety: `key` is owned here and hasn't been wrapped yet.
+            unsafe { bssl_sys::EC_KEY_free(key) };
+            return Err(EcSignerError);
+        }
+        // Safety: `key` and `scalar` are valid; `EC_KEY_set_private_key` copies `scalar` into
+        // `key` and does not take ownership of it.
+        let result = unsafe { bssl_sys::EC_KEY_set_private_key(key, scalar) };
+        // Safety: `scalar` is no longer needed once copied into `key` above.
+        unsafe { bssl_sys::BN_free(scalar) };
+        if result != 1 {
+            // Safety: `key` is owned here and hasn't been wrapped yet.
+            unsafe { bssl_sys::EC_KEY_free(key) };
+            return Err(EcSignerError);
+        }
+
+        // `EC_KEY_set_private_key` does not derive the public point, so compute `scalar * G`
+        // ourselves and attach it, mirroring what `EC_KEY_generate_key` does internally.
+        // Safety: `key`'s group was set by `new_p256_key` and is valid for the lifetime of `key`.
+        let group = unsafe { bssl_sys::EC_KEY_get0_group(key) };
+        // Safety: `group` is valid; `EC_POINT_new` returns an owned point on this group.
+        let public_point = unsafe { bssl_sys::EC_POINT_new(group) };
+        if public_point.is_null() {
+            // Safety: `key` is owned here and hasn't been wrapped yet.
+            unsafe { bssl_sys::EC_KEY_free(key) };
+            return Err(EcSignerError);
+        }
+        // Safety: `group`, `public_point`, and the private key set on `key` are all valid; this
+        // computes `public_point = scalar * G` with no peer point multiplication.
+        let private_key_ptr = unsafe { bssl_sys::EC_KEY_get0_private_key(key) };
+        let result = unsafe {
+            bssl_sys::EC_POINT_mul(
+                group,
+                public_point,
+                private_key_ptr,
+                core::ptr::null(),
+                core::ptr::null(),
+                core::ptr::null_mut(),
+            )
+        };
+        let set_result = if result == 1 {
+            // Safety: `key` and `public_point` are both valid and share `group`.
+            unsafe { bssl_sys::EC_KEY_set_public_key(key, public_point) }
+        } else {
+            0
+        };
+        // Safety: `public_point` was copied by `EC_KEY_set_public_key` above (or is being
+        // discarded after a failed multiplication) and is no longer needed.
+        unsafe { bssl_sys::EC_POINT_free(public_point) };
+        if result != 1 || set_result != 1 {
+            // Safety: `key` is owned here and hasn't been wrapped yet.
+            unsafe { bssl_sys::EC_KEY_free(key) };
+            return Err(EcSignerError);
+        }
+
+        Ok(Self { key })
+    }
+
+    /// Serializes this private key to its raw (big-endian) scalar encoding.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = vec![0u8; PRIVATE_KEY_LEN];
+        // Safety: `self.key` is valid for the lifetime of `self`.
+        let scalar = unsafe { bssl_sys::EC_KEY_get0_private_key(self.key) };
+        // Safety: `scalar` is valid and `out` is sized to `PRIVATE_KEY_LEN`, the fixed width of a
+        // P-256 scalar, which `BN_bn2bin_padded` zero-pads to if the value is shorter.
+        let result = unsafe { bssl_sys::BN_bn2bin_padded(out.as_mut_ptr(), out.len(), scalar) };
+        assert_eq!(result, 1, "P-256 scalar never exceeds PRIVATE_KEY_LEN bytes");
+        out
+    }
+
+    /// Derives the public key corresponding to this private key.
+    pub fn public_key(&self) -> Result<EcPublicKey, EcSignerError> {
+        let pub_key = new_p256_key()?;
+        // Safety: `self.key` is valid for the lifetime of `self`; `EC_KEY_get0_public_key` returns
+        // a non-owning pointer which `EC_KEY_set_public_key` copies into `pub_key`.
+        let point = unsafe { bssl_sys::EC_KEY_get0_public_key(self.key) };
+        let result = unsafe { bssl_sys::EC_KEY_set_public_key(pub_key, point) };
+        if result != 1 {
+            // Safety: `pub_key` is owned here and hasn't been wrapped yet.
+            unsafe { bssl_sys::EC_KEY_free(pub_key) };
+            return Err(EcSignerError);
+        }
+        Ok(EcPublicKey { key: pub_key })
+    }
+
+    /// Signs `data` with ECDSA over its SHA-256 digest, returning a DER-encoded signature.
+    pub fn sign(&self, data: &[u8]) -> Result<Vec<u8>, EcSignerError> {
+        let mut digest = [0u8; 32];
+        // Safety: `digest` is exactly `SHA256_DIGEST_LENGTH` bytes, as `SHA256` requires.
+        unsafe { bssl_sys::SHA256(data.as_ptr(), data.len(), digest.as_mut_ptr()) };
+
+        // Safety: `self.key` is valid for the lifetime of `self`, and `ECDSA_size` only reads it.
+        let max_sig_len = unsafe { bssl_sys::ECDSA_size(self.key) } as usize;
+        let mut sig = vec![0u8; max_sig_len];
+        let mut sig_len: core::ffi::c_uint = 0;
+        // Safety:
+        // - `digest` is exactly 32 bytes, matching the SHA-256 digest ECDSA_sign expects.
+        // - `sig` is sized to `ECDSA_size`'s upper bound and `sig_len` receives the actual length.
+        let result = unsafe {
+            bssl_sys::ECDSA_sign(
+                0,
+                digest.as_ptr(),
+                digest.len(),
+                sig.as_mut_ptr(),
+                &mut sig_len,
+                self.key,
+            )
+        };
+        if result != 1 {
+            return Err(EcSignerError);
+        }
+        sig.truncate(sig_len as usize);
+        Ok(sig)
+    }
+}
+
+impl EcPublicKey {
+    /// Parses a public key from its uncompressed SEC1 encoding (`0x04 || X || Y`).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EcSignerError> {
+        if bytes.len() != PUBLIC_KEY_LEN {
+            return Err(EcSignerError);
+        }
+        let key = new_p256_key()?;
+        let mut key_ptr = key;
+        // Safety: `bytes` points to `bytes.len()` readable bytes; `o2i_ECPublicKey` reads the
+        // point encoding into the already-allocated `key`.
+        let result =
+            unsafe { bssl_sys::o2i_ECPublicKey(&mut key_ptr, &bytes.as_ptr(), bytes.len() as i64) };
+        if result.is_null() {
+            // Safety: `key` is owned here and hasn't been wrapped yet.
+            unsafe { bssl_sys::EC_KEY_free(key) };
+            return Err(EcSignerError);
+        }
+        Ok(Self { key })
+    }
+
+    /// Serializes this public key to its uncompressed SEC1 encoding (`0x04 || X || Y`).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = vec![0u8; PUBLIC_KEY_LEN];
+        // Safety: `self.key` is valid for the lifetime of `self`, and `out` is sized to
+        // `PUBLIC_KEY_LEN`, the encoded length of an uncompressed P-256 point.
+        let written = unsafe {
+            bssl_sys::EC_POINT_point2oct(
+                bssl_sys::EC_KEY_get0_group(self.key),
+                bssl_sys::EC_KEY_get0_public_key(self.key),
+                bssl_sys::point_conversion_form_t::POINT_CONVERSION_UNCOMPRESSED,
+                out.as_mut_ptr(),
+                out.len(),
+                core::ptr::null_mut(),
+            )
+        };
+        out.truncate(written);
+        out
+    }
+
+    /// Verifies a DER-encoded ECDSA signature over the SHA-256 digest of `data`.
+    pub fn verify(&self, data: &[u8], sig: &[u8]) -> Result<(), EcSignerError> {
+        let mut digest = [0u8; 32];
+        // Safety: `digest` is exactly `SHA256_DIGEST_LENGTH` bytes, as `SHA256` requires.
+        unsafe { bssl_sys::SHA256(data.as_ptr(), data.len(), digest.as_mut_ptr()) };
+
+        // Safety: `digest` is exactly 32 bytes, `sig` points to `sig.len()` readable bytes, and
+        // `self.key` is valid for the lifetime of `self`.
+        let result = unsafe {
+            bssl_sys::ECDSA_verify(
+                0,
+                digest.as_ptr(),
+                digest.len(),
+                sig.as_ptr(),
+                sig.len(),
+                self.key,
+            )
+        };
+        if result != 1 {
+            return Err(EcSignerError);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for EcPrivateKey {
+    fn drop(&mut self) {
+        // Safety: `self.key` is owned by this struct.
+        unsafe { bssl_sys::EC_KEY_free(self.key) }
+    }
+}
+
+impl Drop for EcPublicKey {
+    fn drop(&mut self) {
+        // Safety: `self.key` is owned by this struct.
+        unsafe { bssl_sys::EC_KEY_free(self.key) }
+    }
+}
+
+/// secp256k1 ECDSA with Ethereum/Bitcoin-style public-key recovery.
+///
+/// BoringSSL's `ECDSA_do_sign` doesn't expose the ephemeral nonce or curve point `R` it used, so
+/// [`Secp256k1PrivateKey::sign_recoverable`] doesn't compute the recovery id analytically from
+/// them. Instead, after normalizing `s` to the curve's low half, it tries each of the (up to)
+/// four candidate recovery ids through [`recover`] and keeps the one that reconstructs this
+/// key's own public key. This is equivalent to deriving `v` from `R`'s parity directly, since
+/// that parity is exactly what distinguishes the four candidates.
+pub mod secp256k1 {
+    use super::EcSignerError;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    /// Length in bytes of an uncompressed SEC1 secp256k1 public key point (`0x04 || X || Y`).
+    pub const PUBLIC_KEY_LEN: usize = 65;
+
+    /// Length in bytes of a raw (big-endian, unencoded) secp256k1 private key scalar.
+    pub const PRIVATE_KEY_LEN: usize = 32;
+
+    /// Length in bytes of a recoverable signature (`r || s || v`).
+    pub const RECOVERABLE_SIGNATURE_LEN: usize = 65;
+
+    /// An owned, non-null BoringSSL `BIGNUM` that is freed on drop.
+    struct Bn(*mut bssl_sys::BIGNUM);
+
+    impl Bn {
+        fn new() -> Result<Self, EcSignerError> {
+            // Safety: `BN_new` either returns a valid pointer or null.
+            let bn = unsafe { bssl_sys::BN_new() };
+            if bn.is_null() {
+                return Err(EcSignerError);
+            }
+            Ok(Self(bn))
+        }
+
+        fn from_bytes(bytes: &[u8]) -> Result<Self, EcSignerError> {
+            // Safety: `bytes` points to `bytes.len()` readable bytes; `BN_bin2bn` allocates a new
+            // `BIGNUM` and does not retain a reference to `bytes`.
+            let bn = unsafe { bssl_sys::BN_bin2bn(bytes.as_ptr(), bytes.len(), core::ptr::null_mut()) };
+            if bn.is_null() {
+                return Err(EcSignerError);
+            }
+            Ok(Self(bn))
+        }
+
+        fn as_ptr(&self) -> *const bssl_sys::BIGNUM {
+            self.0
+        }
+
+        fn as_mut_ptr(&mut self) -> *mut bssl_sys::BIGNUM {
+            self.0
+        }
+
+        fn is_zero(&self) -> bool {
+            // Safety: `self.0` is valid for the lifetime of `self`.
+            unsafe { bssl_sys::BN_is_zero(self.0) != 0 }
+        }
+
+        fn to_bytes_padded(&self, len: usize) -> Result<Vec<u8>, EcSignerError> {
+            let mut out = vec![0u8; len];
+            // Safety: `self.0` is valid, and `out` is sized to `len`, which the caller guarantees
+            // is large enough for the value (e.g. a curve order-sized scalar).
+            let result = unsafe { bssl_sys::BN_bn2bin_padded(out.as_mut_ptr(), out.len(), self.0) };
+            if result != 1 {
+                return Err(EcSignerError);
+            }
+            Ok(out)
+        }
+    }
+
+    impl Drop for Bn {
+        fn drop(&mut self) {
+            // Safety: `self.0` is owned by this struct.
+            unsafe { bssl_sys::BN_free(self.0) }
+        }
+    }
+
+    /// An owned, non-null BoringSSL `BN_CTX` scratch space, freed on drop.
+    struct BnCtx(*mut bssl_sys::BN_CTX);
+
+    impl BnCtx {
+        fn new() -> Result<Self, EcSignerError> {
+            // Safety: `BN_CTX_new` either returns a valid pointer or null.
+            let ctx = unsafe { bssl_sys::BN_CTX_new() };
+            if ctx.is_null() {
+                return Err(EcSignerError);
+            }
+            Ok(Self(ctx))
+        }
+
+        fn as_mut_ptr(&mut self) -> *mut bssl_sys::BN_CTX {
+            self.0
+        }
+    }
+
+    impl Drop for BnCtx {
+        fn drop(&mut self) {
+            // Safety: `self.0` is owned by this struct.
+            unsafe { bssl_sys::BN_CTX_free(self.0) }
+        }
+    }
+
+    /// An owned, non-null BoringSSL `EC_POINT`, freed on drop.
+    struct Point(*mut bssl_sys::EC_POINT);
+
+    impl Point {
+        fn new(group: *const bssl_sys::EC_GROUP) -> Result<Self, EcSignerError> {
+            // Safety: `group` is valid for the duration of this call.
+            let point = unsafe { bssl_sys::EC_POINT_new(group) };
+            if point.is_null() {
+                return Err(EcSignerError);
+            }
+            Ok(Self(point))
+        }
+
+        fn as_ptr(&self) -> *const bssl_sys::EC_POINT {
+            self.0
+        }
+
+        fn as_mut_ptr(&mut self) -> *mut bssl_sys::EC_POINT {
+            self.0
+        }
+    }
+
+    impl Drop for Point {
+        fn drop(&mut self) {
+            // Safety: `self.0` is owned by this struct.
+            unsafe { bssl_sys::EC_POINT_free(self.0) }
+        }
+    }
+
+    fn new_secp256k1_key() -> Result<*mut bssl_sys::EC_KEY, EcSignerError> {
+        // Safety: `NID_secp256k1` is a valid, constant curve identifier.
+        let key = unsafe { bssl_sys::EC_KEY_new_by_curve_name(bssl_sys::NID_secp256k1) };
+        if key.is_null() {
+            return Err(EcSignerError);
+        }
+        Ok(key)
+    }
+
+    /// A secp256k1 private key used to produce recoverable ECDSA signatures.
+    pub struct Secp256k1PrivateKey {
+        key: *mut bssl_sys::EC_KEY,
+    }
+
+    /// A secp256k1 public key, either supplied directly or reconstructed via [`recover`].
+    pub struct Secp256k1PublicKey {
+        key: *mut bssl_sys::EC_KEY,
+    }
+
+    impl Secp256k1PrivateKey {
+        /// Generates a new random secp256k1 private key.
+        pub fn generate() -> Result<Self, EcSignerError> {
+            let key = new_secp256k1_key()?;
+            // Safety: `key` was just allocated and checked non-null above.
+            let result = unsafe { bssl_sys::EC_KEY_generate_key(key) };
+            if result != 1 {
+                // Safety: `key` is owned here and hasn't been wrapped yet.
+                unsafe { bssl_sys::EC_KEY_free(key) };
+                return Err(EcSignerError);
+            }
+            Ok(Self { key })
+        }
+
+        /// Parses a private key from its raw (big-endian) scalar encoding.
+        pub fn from_bytes(bytes: &[u8]) -> Result<Self, EcSignerError> {
+            if bytes.len() != PRIVATE_KEY_LEN {
+                return Err(EcSignerError);
+            }
+            let key = new_secp256k1_key()?;
+            let scalar = Bn::from_bytes(bytes)?;
+            // Safety: `key` takes ownership of a copy of `scalar`'s value; `scalar` itself is
+            // freed independently when it drops.
+            let result = unsafe { bssl_sys::EC_KEY_set_private_key(key, scalar.as_ptr() as *mut _) };
+            if result != 1 {
+                // Safety: `key` is owned here and hasn't been wrapped yet.
+                unsafe { bssl_sys::EC_KEY_free(key) };
+                return Err(EcSignerError);
+            }
+
+            // Safety: `key`'s group was set by `new_secp256k1_key` and is valid here.
+            let group = unsafe { bssl_sys::EC_KEY_get0_group(key) };
+            let mut public_point = match Point::new(group) {
+                Ok(point) => point,
+                Err(e) => {
+                    // Safety: `key` is owned here and hasn't been wrapped yet.
+                    unsafe { bssl_sys::EC_KEY_free(key) };
+                    return Err(e);
+                }
+            };
+            // Safety: `group`, `public_point`, and `scalar` are all valid; this computes
+            // `public_point = scalar * G` with no peer point multiplication.
+            let result = unsafe {
+                bssl_sys::EC_POINT_mul(
+                    group,
+                    public_point.as_mut_ptr(),
+                    scalar.as_ptr(),
+                    core::ptr::null(),
+                    core::ptr::null(),
+                    core::ptr::null_mut(),
+                )
+            };
+            let set_result = if result == 1 {
+                // Safety: `key` and `public_point` are both valid and share `group`.
+                unsafe { bssl_sys::EC_KEY_set_public_key(key, public_point.as_ptr()) }
+            } else {
+                0
+            };
+            if result != 1 || set_result != 1 {
+                // Safety: `key` is owned here and hasn't been wrapped yet.
+                unsafe { bssl_sys::EC_KEY_free(key) };
+                return Err(EcSignerError);
+            }
+
+            Ok(Self { key })
+        }
+
+        /// Serializes this private key to its raw (big-endian) scalar encoding.
+        pub fn to_bytes(&self) -> Result<Vec<u8>, EcSignerError> {
+            // Safety: `self.key` is valid for the lifetime of `self`.
+            let scalar_ptr = unsafe { bssl_sys::EC_KEY_get0_private_key(self.key) };
+            let mut out = vec![0u8; PRIVATE_KEY_LEN];
+            // Safety: `scalar_ptr` is valid and `out` is sized to `PRIVATE_KEY_LEN`, the fixed
+            // width of a secp256k1 scalar.
+            let result =
+                unsafe { bssl_sys::BN_bn2bin_padded(out.as_mut_ptr(), out.len(), scalar_ptr) };
+            if result != 1 {
+                return Err(EcSignerError);
+            }
+            Ok(out)
+        }
+
+        /// Derives the public key corresponding to this private key.
+        pub fn public_key(&self) -> Result<Secp256k1PublicKey, EcSignerError> {
+            let pub_key = new_secp256k1_key()?;
+            // Safety: `self.key` is valid for the lifetime of `self`; `EC_KEY_get0_public_key`
+            // returns a non-owning pointer which `EC_KEY_set_public_key` copies into `pub_key`.
+            let point = unsafe { bssl_sys::EC_KEY_get0_public_key(self.key) };
+            let result = unsafe { bssl_sys::EC_KEY_set_public_key(pub_key, point) };
+            if result != 1 {
+                // Safety: `pub_key` is owned here and hasn't been wrapped yet.
+                unsafe { bssl_sys::EC_KEY_free(pub_key) };
+                return Err(EcSignerError);
+            }
+            Ok(Secp256k1PublicKey { key: pub_key })
+        }
+
+        /// Signs `msg_hash`, a 32-byte digest computed by the caller, producing a recoverable
+        /// signature `r || s || v`, where `v` is the 2-bit recovery id described on [`recover`].
+        /// `s` is normalized to the curve order's low half to keep signatures canonical.
+        pub fn sign_recoverable(&self, msg_hash: &[u8; 32]) -> Result<[u8; 65], EcSignerError> {
+            // Safety: `msg_hash` is exactly 32 bytes and `self.key` is valid for `self`'s
+            // lifetime; `ECDSA_do_sign` returns an owned `ECDSA_SIG` or null on failure.
+            let sig =
+                unsafe { bssl_sys::ECDSA_do_sign(msg_hash.as_ptr(), msg_hash.len(), self.key) };
+            if sig.is_null() {
+                return Err(EcSignerError);
+            }
+            let mut r_ptr: *const bssl_sys::BIGNUM = core::ptr::null();
+            let mut s_ptr: *const bssl_sys::BIGNUM = core::ptr::null();
+            // Safety: `sig` is valid; `ECDSA_SIG_get0` returns non-owning pointers into it.
+            unsafe { bssl_sys::ECDSA_SIG_get0(sig, &mut r_ptr, &mut s_ptr) };
+
+            let group = unsafe { bssl_sys::EC_KEY_get0_group(self.key) };
+            // Safety: `group` is valid for `self`'s lifetime; this returns a non-owning pointer.
+            let order = unsafe { bssl_sys::EC_GROUP_get0_order(group) };
+
+            let r = Bn::new().and_then(|mut r| {
+                // Safety: `r_ptr` is valid for the duration of this call.
+                if unsafe { bssl_sys::BN_copy(r.as_mut_ptr(), r_ptr) }.is_null() {
+                    return Err(EcSignerError);
+                }
+                Ok(r)
+            });
+            let s = Bn::new().and_then(|mut s| {
+                // Safety: `s_ptr` is valid for the duration of this call.
+                if unsafe { bssl_sys::BN_copy(s.as_mut_ptr(), s_ptr) }.is_null() {
+                    return Err(EcSignerError);
+                }
+                Ok(s)
+            });
+            // Safety: `sig` is owned here and both `r`/`s` have been copied out of it above.
+            unsafe { bssl_sys::ECDSA_SIG_free(sig) };
+            let (r, mut s) = match (r, s) {
+                (Ok(r), Ok(s)) => (r, s),
+                _ => return Err(EcSignerError),
+            };
+
+            // Normalize `s` to the curve order's low half: if `s > n/2`, replace it with `n - s`.
+            let mut half_order = Bn::new()?;
+            // Safety: `order` is valid; `half_order` was freshly allocated above.
+            if unsafe { bssl_sys::BN_rshift1(half_order.as_mut_ptr(), order) } != 1 {
+                return Err(EcSignerError);
+            }
+            // Safety: both operands are valid.
+            if unsafe { bssl_sys::BN_cmp(s.as_ptr(), half_order.as_ptr()) } > 0 {
+                // Safety: `order`, `s`, and the output all point to valid `BIGNUM`s.
+                if unsafe { bssl_sys::BN_sub(s.as_mut_ptr(), order, s.as_ptr()) } != 1 {
+                    return Err(EcSignerError);
+                }
+            }
+
+            let r_bytes = r.to_bytes_padded(PRIVATE_KEY_LEN)?;
+            let s_bytes = s.to_bytes_padded(PRIVATE_KEY_LEN)?;
+            let expected_public_key = self.public_key()?.to_bytes()?;
+
+            // BoringSSL's ECDSA_do_sign doesn't expose which candidate curve point `R` it used,
+            // so recompute each candidate and keep the recovery id that reconstructs this key's
+            // own public key; see the module-level doc comment.
+            for v in 0u8..4 {
+                let mut candidate = [0u8; 65];
+                candidate[..32].copy_from_slice(&r_bytes);
+                candidate[32..64].copy_from_slice(&s_bytes);
+                candidate[64] = v;
+                if let Ok(recovered) = recover(msg_hash, &candidate) {
+                    if recovered.to_bytes()? == expected_public_key {
+                        return Ok(candidate);
+                    }
+                }
+            }
+            Err(EcSignerError)
+        }
+    }
+
+    impl Secp256k1PublicKey {
+        /// Serializes this public key to its uncompressed SEC1 encoding (`0x04 || X || Y`).
+        pub fn to_bytes(&self) -> Result<Vec<u8>, EcSignerError> {
+            let mut out = vec![0u8; PUBLIC_KEY_LEN];
+            // Safety: `self.key` is valid for the lifetime of `self`, and `out` is sized to
+            // `PUBLIC_KEY_LEN`, the encoded length of an uncompressed secp256k1 point.
+            let written = unsafe {
+                bssl_sys::EC_POINT_point2oct(
+                    bssl_sys::EC_KEY_get0_group(self.key),
+                    bssl_sys::EC_KEY_get0_public_key(self.key),
+                    bssl_sys::point_conversion_form_t::POINT_CONVERSION_UNCOMPRESSED,
+                    out.as_mut_ptr(),
+                    out.len(),
+                    core::ptr::null_mut(),
+                )
+            };
+            if written != PUBLIC_KEY_LEN {
+                return Err(EcSignerError);
+            }
+            Ok(out)
+        }
+    }
+
+    impl Drop for Secp256k1PrivateKey {
+        fn drop(&mut self) {
+            // Safety: `self.key` is owned by this struct.
+            unsafe { bssl_sys::EC_KEY_free(self.key) }
+        }
+    }
+
+    impl Drop for Secp256k1PublicKey {
+        fn drop(&mut self) {
+            // Safety: `self.key` is owned by this struct.
+            unsafe { bssl_sys::EC_KEY_free(self.key) }
+        }
+    }
+
+    /// Reconstructs the public key that produced `sig` (`r || s || v`) over `msg_hash`, a 32-byte
+    /// digest computed by the caller, without knowing that key in advance.
+    ///
+    /// `v`'s low bit is the parity of the y-coordinate of the curve point `R` used while signing;
+    /// its next bit is set if `r` had overflowed the field and needed the curve order added back
+    /// in. `r` and `s` are each rejected if zero or `>= n`, the curve order.
+    pub fn recover(msg_hash: &[u8; 32], sig: &[u8; 65]) -> Result<Secp256k1PublicKey, EcSignerError> {
+        let v = sig[64];
+        if v > 3 {
+            return Err(EcSignerError);
+        }
+
+        let key = new_secp256k1_key()?;
+        let group = unsafe { bssl_sys::EC_KEY_get0_group(key) };
+        // Safety: `group` is valid; these both return non-owning pointers.
+        let order = unsafe { bssl_sys::EC_GROUP_get0_order(group) };
+        let mut ctx = match BnCtx::new() {
+            Ok(ctx) => ctx,
+            Err(e) => {
+                // Safety: `key` is owned here and hasn't been wrapped yet.
+                unsafe { bssl_sys::EC_KEY_free(key) };
+                return Err(e);
+            }
+        };
+
+        let mut recover_inner = || -> Result<Point, EcSignerError> {
+            let r = Bn::from_bytes(&sig[0..32])?;
+            let s = Bn::from_bytes(&sig[32..64])?;
+            if r.is_zero() || s.is_zero() {
+                return Err(EcSignerError);
+            }
+            // Safety: both operands are valid.
+            if unsafe { bssl_sys::BN_cmp(r.as_ptr(), order) } >= 0
+                || unsafe { bssl_sys::BN_cmp(s.as_ptr(), order) } >= 0
+            {
+                return Err(EcSignerError);
+            }
+
+            // x = r, or r + n if the field-overflow bit is set.
+            let mut x = Bn::new()?;
+            // Safety: `r` is valid; `x` was freshly allocated.
+            if unsafe { bssl_sys::BN_copy(x.as_mut_ptr(), r.as_ptr()) }.is_null() {
+                return Err(EcSignerError);
+            }
+            if v & 2 != 0 {
+                // Safety: `x` and `order` are valid.
+                if unsafe { bssl_sys::BN_add(x.as_mut_ptr(), x.as_ptr(), order) } != 1 {
+                    return Err(EcSignerError);
+                }
+            }
+
+            // R = the curve point with x-coordinate `x` and y-parity from `v`'s low bit.
+            let mut r_point = Point::new(group)?;
+            let y_bit = (v & 1) as i32;
+            // Safety: `group`, `r_point`, and `x` are all valid.
+            let result = unsafe {
+                bssl_sys::EC_POINT_set_compressed_coordinates_GFp(
+                    group,
+                    r_point.as_mut_ptr(),
+                    x.as_ptr(),
+                    y_bit,
+                    ctx.as_mut_ptr(),
+                )
+            };
+            if result != 1 {
+                return Err(EcSignerError);
+            }
+
+            // e = msg_hash reduced mod the curve order.
+            let hash = Bn::from_bytes(msg_hash)?;
+            let mut e = Bn::new()?;
+            // Safety: all operands are valid.
+            if unsafe { bssl_sys::BN_nnmod(e.as_mut_ptr(), hash.as_ptr(), order, ctx.as_mut_ptr()) }
+                != 1
+            {
+                return Err(EcSignerError);
+            }
+
+            // r_inv = r^-1 mod n
+            let mut r_inv = Bn::new()?;
+            // Safety: all operands are valid; `BN_mod_inverse` writes into the provided `BIGNUM`.
+            if unsafe {
+                bssl_sys::BN_mod_inverse(r_inv.as_mut_ptr(), r.as_ptr(), order, ctx.as_mut_ptr())
+            }
+            .is_null()
+            {
+                return Err(EcSignerError);
+            }
+
+            // u1 = -e * r_inv mod n
+            let mut u1 = Bn::new()?;
+            // Safety: all operands are valid.
+            if unsafe {
+                bssl_sys::BN_mod_mul(u1.as_mut_ptr(), e.as_ptr(), r_inv.as_ptr(), order, ctx.as_mut_ptr())
+            } != 1
+            {
+                return Err(EcSignerError);
+            }
+            // Safety: all operands are valid.
+            if unsafe { bssl_sys::BN_sub(u1.as_mut_ptr(), order, u1.as_ptr()) } != 1 {
+                return Err(EcSignerError);
+            }
+            // Safety: all operands are valid.
+            if unsafe { bssl_sys::BN_nnmod(u1.as_mut_ptr(), u1.as_ptr(), order, ctx.as_mut_ptr()) }
+                != 1
+            {
+                return Err(EcSignerError);
+            }
+
+            // u2 = s * r_inv mod n
+            let mut u2 = Bn::new()?;
+            // Safety: all operands are valid.
+            if unsafe {
+                bssl_sys::BN_mod_mul(u2.as_mut_ptr(), s.as_ptr(), r_inv.as_ptr(), order, ctx.as_mut_ptr())
+            } != 1
+            {
+                return Err(EcSignerError);
+            }
+
+            // Q = u1*G + u2*R
+            let mut q = Point::new(group)?;
+            // Safety: `group`, `q`, `u1`, `r_point`, and `u2` are all valid.
+            let result = unsafe {
+                bssl_sys::EC_POINT_mul(
+                    group,
+                    q.as_mut_ptr(),
+                    u1.as_ptr(),
+                    r_point.as_ptr(),
+                    u2.as_ptr(),
+                    ctx.as_mut_ptr(),
+                )
+            };
+            if result != 1 {
+                return Err(EcSignerError);
+            }
+            // Safety: `group` and `q` are valid.
+            if unsafe { bssl_sys::EC_POINT_is_at_infinity(group, q.as_ptr()) } != 0 {
+                return Err(EcSignerError);
+            }
+            Ok(q)
+        };
+
+        let q = match recover_inner() {
+            Ok(q) => q,
+            Err(e) => {
+                // Safety: `key` is owned here and hasn't been wrapped yet.
+                unsafe { bssl_sys::EC_KEY_free(key) };
+                return Err(e);
+            }
+        };
+        // Safety: `key` and `q` are both valid and share `group`.
+        let result = unsafe { bssl_sys::EC_KEY_set_public_key(key, q.as_ptr()) };
+        if result != 1 {
+            // Safety: `key` is owned here and hasn't been wrapped yet.
+            unsafe { bssl_sys::EC_KEY_free(key) };
+            return Err(EcSignerError);
+        }
+        Ok(Secp256k1PublicKey { key })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn sign_then_recover_round_trips() {
+            let private_key = Secp256k1PrivateKey::generate().expect("key generation should succeed");
+            let msg_hash = [0x42u8; 32];
+            let sig = private_key
+                .sign_recoverable(&msg_hash)
+                .expect("signing should succeed");
+
+            let recovered = recover(&msg_hash, &sig).expect("recovery should succeed");
+            assert_eq!(
+                recovered.to_bytes().expect("serialization should succeed"),
+                private_key
+                    .public_key()
+                    .expect("public key derivation should succeed")
+                    .to_bytes()
+                    .expect("serialization should succeed")
+            );
+        }
+
+        #[test]
+        fn recover_rejects_zero_r() {
+            let sig = [0u8; 65];
+            assert!(recover(&[0x11u8; 32], &sig).is_err());
+        }
+
+        #[test]
+        fn recover_rejects_out_of_range_v() {
+            let private_key = Secp256k1PrivateKey::generate().expect("key generation should succeed");
+            let msg_hash = [0x42u8; 32];
+            let mut sig = private_key
+                .sign_recoverable(&msg_hash)
+                .expect("signing should succeed");
+            sig[64] = 4;
+            assert!(recover(&msg_hash, &sig).is_err());
+        }
+
+        #[test]
+        fn private_key_bytes_round_trip() {
+            let private_key = Secp256k1PrivateKey::generate().expect("key generation should succeed");
+            let bytes = private_key.to_bytes().expect("serialization should succeed");
+            assert_eq!(bytes.len(), PRIVATE_KEY_LEN);
+            let restored = Secp256k1PrivateKey::from_bytes(&bytes).expect("parsing should succeed");
+            assert_eq!(
+                restored.public_key().expect("public key derivation should succeed").to_bytes().expect("serialization should succeed"),
+                private_key.public_key().expect("public key derivation should succeed").to_bytes().expect("serialization should succeed")
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_then_verify_round_trips() {
+        let private_key = EcPrivateKey::generate().expect("key generation should succeed");
+        let public_key = private_key.public_key().expect("public key derivation should succeed");
+        let sig = private_key.sign(b"hello world").expect("signing should succeed");
+        public_key.verify(b"hello world", &sig).expect("verification should succeed");
+    }
+
+    #[test]
+    fn verify_rejects_tampered_message() {
+        let private_key = EcPrivateKey::generate().expect("key generation should succeed");
+        let public_key = private_key.public_key().expect("public key derivation should succeed");
+        let sig = private_key.sign(b"hello world").expect("signing should succeed");
+        assert!(public_key.verify(b"goodbye world", &sig).is_err());
+    }
+
+    #[test]
+    fn private_key_bytes_round_trip() {
+        let private_key = EcPrivateKey::generate().expect("key generation should succeed");
+        let expected_public = private_key
+            .public_key()
+            .expect("public key derivation should succeed")
+            .to_bytes();
+        let bytes = private_key.to_bytes();
+        assert_eq!(bytes.len(), PRIVATE_KEY_LEN);
+
+        let restored = EcPrivateKey::from_bytes(&bytes).expect("parsing should succeed");
+        assert_eq!(
+            restored.public_key().expect("public key derivation should succeed").to_bytes(),
+            expected_public
+        );
+
+        let sig = restored.sign(b"hello world").expect("signing should succeed");
+        private_key
+            .public_key()
+            .expect("public key derivation should succeed")
+            .verify(b"hello world", &sig)
+            .expect("verification should succeed");
+    }
+
+    #[test]
+    fn public_key_bytes_round_trip() {
+        let private_key = EcPrivateKey::generate().expect("key generation should succeed");
+        let public_key = private_key.public_key().expect("public key derivation should succeed");
+        let bytes = public_key.to_bytes();
+        assert_eq!(bytes.len(), PUBLIC_KEY_LEN);
+        let parsed = EcPublicKey::from_bytes(&bytes).expect("parsing should succeed");
+        assert_eq!(parsed.to_bytes(), bytes);
+    }
+}
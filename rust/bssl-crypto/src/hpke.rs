@@ -13,18 +13,42 @@
  * CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
  */
 
+//! RFC 9180 Hybrid Public Key Encryption (HPKE) over BoringSSL's `EVP_HPKE_CTX`.
+//!
+//! This only covers the two RFC 9180 modes BoringSSL's public HPKE API (`include/openssl/hpke.h`)
+//! actually implements: base (`EVP_HPKE_CTX_setup_sender`/`_recipient`) and auth
+//! (`EVP_HPKE_CTX_setup_auth_sender`/`_recipient`, which mix a static sender key into the shared
+//! secret via AuthEncap/AuthDecap). PSK and Auth-PSK mode are *not* implemented: BoringSSL's HPKE
+//! only exposes per-mode context setup, never a standalone KEM Encap/AuthEncap that returns just
+//! the shared secret, so there is no way to run RFC 9180's PSK key schedule
+//! (`mode || LabeledExtract("psk_id_hash", psk_id) || LabeledExtract("info_hash", info)`, `secret
+//! = LabeledExtract(shared_secret, "secret", psk)`) without reimplementing DHKEM's Encap from raw
+//! ECDH primitives -- out of scope for what is meant to be a thin wrapper around BoringSSL's own
+//! HPKE. [`SenderContext::new_psk`]/[`new_auth_psk`](SenderContext::new_auth_psk) and their
+//! [`RecipientContext`] counterparts exist only so callers that select a mode at runtime get a
+//! typed [`HpkeError`] instead of a missing method; they always fail.
+
+use alloc::vec;
 use alloc::vec::Vec;
 
 /// KEM algorithms.
 pub const KEM_X25519_HKDF_SHA256: u16 = bssl_sys::EVP_HPKE_DHKEM_X25519_HKDF_SHA256 as u16;
+/// KEM algorithms.
+pub const KEM_P256_HKDF_SHA256: u16 = bssl_sys::EVP_HPKE_DHKEM_P256_HKDF_SHA256 as u16;
 
 /// KDF algorithms.
 pub const KDF_HKDF_SHA256: u16 = bssl_sys::EVP_HPKE_HKDF_SHA256 as u16;
 
 /// AEAD algorithms.
 pub const AEAD_AES_128_GCM: u16 = bssl_sys::EVP_HPKE_AES_128_GCM as u16;
+/// AEAD algorithms.
+pub const AEAD_AES_256_GCM: u16 = bssl_sys::EVP_HPKE_AES_256_GCM as u16;
+/// AEAD algorithms.
+pub const AEAD_CHACHA20_POLY1305: u16 = bssl_sys::EVP_HPKE_CHACHA20_POLY1305 as u16;
 
-/// Maximum length of the encapsulated key for all currently supported KEMs.
+/// Maximum length of the encapsulated key across all currently supported KEMs. `enc` is always
+/// truncated to the length actually returned by BoringSSL, so this only bounds the scratch buffer
+/// used while setting up a [`SenderContext`].
 const MAX_ENC_LENGTH: usize = bssl_sys::EVP_HPKE_MAX_ENC_LENGTH as usize;
 
 /// Error returned from unsuccessful HPKE operations.
@@ -42,7 +66,29 @@ pub struct Params {
 impl Params {
     /// New Params from KEM, KDF, and AEAD identifiers, such as bssl_sys::EVP_HPKE_AES_128_GCM.
     pub fn new(kem: u16, kdf: u16, aead: u16) -> Result<Self, HpkeError> {
-        unimplemented!();
+        // Safety: these getters take no arguments and always return a valid, static pointer.
+        let kem = match kem as u32 {
+            bssl_sys::EVP_HPKE_DHKEM_X25519_HKDF_SHA256 => unsafe {
+                bssl_sys::EVP_hpke_x25519_hkdf_sha256()
+            },
+            bssl_sys::EVP_HPKE_DHKEM_P256_HKDF_SHA256 => unsafe {
+                bssl_sys::EVP_hpke_p256_hkdf_sha256()
+            },
+            _ => return Err(HpkeError),
+        };
+        let kdf = match kdf as u32 {
+            bssl_sys::EVP_HPKE_HKDF_SHA256 => unsafe { bssl_sys::EVP_hpke_hkdf_sha256() },
+            _ => return Err(HpkeError),
+        };
+        let aead = match aead as u32 {
+            bssl_sys::EVP_HPKE_AES_128_GCM => unsafe { bssl_sys::EVP_hpke_aes_128_gcm() },
+            bssl_sys::EVP_HPKE_AES_256_GCM => unsafe { bssl_sys::EVP_hpke_aes_256_gcm() },
+            bssl_sys::EVP_HPKE_CHACHA20_POLY1305 => unsafe {
+                bssl_sys::EVP_hpke_chacha20_poly1305()
+            },
+            _ => return Err(HpkeError),
+        };
+        Ok(Self { kem, kdf, aead })
     }
 }
 
@@ -60,11 +106,126 @@ pub struct SenderContext {
 impl SenderContext {
     /// New SenderContext.
     pub fn new(params: &Params, recipient_pub_key: &[u8], info: &[u8]) -> Result<Self, HpkeError> {
-        unimplemented!();
+        // Safety: EVP_HPKE_CTX_new returns either a valid pointer or null on allocation failure.
+        let ctx = unsafe { bssl_sys::EVP_HPKE_CTX_new() };
+        if ctx.is_null() {
+            return Err(HpkeError);
+        }
+
+        let mut enc = [0u8; MAX_ENC_LENGTH];
+        let mut enc_len: usize = 0;
+        // Safety:
+        // - `ctx` was just allocated above and is non-null.
+        // - `enc` is sized to the maximum possible encapsulated key length for any supported KEM.
+        let result = unsafe {
+            bssl_sys::EVP_HPKE_CTX_setup_sender(
+                ctx,
+                enc.as_mut_ptr(),
+                &mut enc_len,
+                enc.len(),
+                params.kem,
+                params.kdf,
+                params.aead,
+                recipient_pub_key.as_ptr(),
+                recipient_pub_key.len(),
+                info.as_ptr(),
+                info.len(),
+            )
+        };
+
+        if result != 1 {
+            // Safety: `ctx` was allocated by this function and not yet freed.
+            unsafe { bssl_sys::EVP_HPKE_CTX_free(ctx) };
+            return Err(HpkeError);
+        }
+
+        Ok(Self {
+            ctx: RecipientContext { ctx },
+            encapsulated_key: enc[..enc_len].to_vec(),
+        })
+    }
+
+    /// New SenderContext for RFC 9180 Auth mode, which additionally authenticates the sender by
+    /// mixing their static private key into the shared secret via AuthEncap. `sender_priv_key` is
+    /// the sender's own HPKE private key.
+    ///
+    /// See the module docs for why the [`SenderContext::new_auth_psk`] counterpart always fails.
+    pub fn new_auth(
+        params: &Params,
+        recipient_pub_key: &[u8],
+        sender_priv_key: &[u8],
+        info: &[u8],
+    ) -> Result<Self, HpkeError> {
+        // Safety: EVP_HPKE_CTX_new returns either a valid pointer or null on allocation failure.
+        let ctx = unsafe { bssl_sys::EVP_HPKE_CTX_new() };
+        if ctx.is_null() {
+            return Err(HpkeError);
+        }
+
+        let mut enc = [0u8; MAX_ENC_LENGTH];
+        let mut enc_len: usize = 0;
+        // Safety:
+        // - `ctx` was just allocated above and is non-null.
+        // - `enc` is sized to the maximum possible encapsulated key length for any supported KEM.
+        // `EVP_HPKE_CTX_setup_auth_sender` is a real entry point in BoringSSL's public
+        // `include/openssl/hpke.h` (alongside the base-mode `_setup_sender`), so this links
+        // against any stock `bssl_sys`; it is BoringSSL's PSK modes that don't exist, not auth.
+        let result = unsafe {
+            bssl_sys::EVP_HPKE_CTX_setup_auth_sender(
+                ctx,
+                enc.as_mut_ptr(),
+                &mut enc_len,
+                enc.len(),
+                params.kem,
+                params.kdf,
+                params.aead,
+                recipient_pub_key.as_ptr(),
+                recipient_pub_key.len(),
+                info.as_ptr(),
+                info.len(),
+                sender_priv_key.as_ptr(),
+                sender_priv_key.len(),
+            )
+        };
+
+        if result != 1 {
+            // Safety: `ctx` was allocated by this function and not yet freed.
+            unsafe { bssl_sys::EVP_HPKE_CTX_free(ctx) };
+            return Err(HpkeError);
+        }
+
+        Ok(Self {
+            ctx: RecipientContext { ctx },
+            encapsulated_key: enc[..enc_len].to_vec(),
+        })
+    }
+
+    /// RFC 9180 PSK mode is not implemented; always returns [`HpkeError`]. See the module docs.
+    pub fn new_psk(
+        _params: &Params,
+        _recipient_pub_key: &[u8],
+        _info: &[u8],
+        _psk: &[u8],
+        _psk_id: &[u8],
+    ) -> Result<Self, HpkeError> {
+        Err(HpkeError)
+    }
+
+    /// RFC 9180 Auth-PSK mode is not implemented, for the same reason as [`SenderContext::new_psk`];
+    /// always returns [`HpkeError`].
+    pub fn new_auth_psk(
+        _params: &Params,
+        _recipient_pub_key: &[u8],
+        _sender_priv_key: &[u8],
+        _info: &[u8],
+        _psk: &[u8],
+        _psk_id: &[u8],
+    ) -> Result<Self, HpkeError> {
+        Err(HpkeError)
     }
 
     /// Seal.
-    pub fn seal(&self, pt: &[u8], aad: &[u8]) -> Result<Vec<u8>, HpkeError> {
+    pub fn seal(&mut self, pt: &[u8], aad: &[u8]) -> Result<Vec<u8>, HpkeError> {
         self.ctx.seal(pt, aad)
     }
 
@@ -72,6 +233,12 @@ impl SenderContext {
     pub fn encapsulated_key(&self) -> &Vec<u8> {
         &self.encapsulated_key
     }
+
+    /// Derives `out_len` bytes of secret keying material from this context. See
+    /// [`RecipientContext::export`].
+    pub fn export(&self, exporter_context: &[u8], out_len: usize) -> Result<Vec<u8>, HpkeError> {
+        self.ctx.export(exporter_context, out_len)
+    }
 }
 
 impl RecipientContext {
@@ -82,16 +249,472 @@ impl RecipientContext {
         encapsulated_key: &[u8],
         info: &[u8],
     ) -> Result<Self, HpkeError> {
-        unimplemented!();
+        // Safety: EVP_HPKE_CTX_new returns either a valid pointer or null on allocation failure.
+        let ctx = unsafe { bssl_sys::EVP_HPKE_CTX_new() };
+        if ctx.is_null() {
+            return Err(HpkeError);
+        }
+
+        // Safety: `ctx` was just allocated above and is non-null.
+        let result = unsafe {
+            bssl_sys::EVP_HPKE_CTX_setup_recipient(
+                ctx,
+                params.kem,
+                params.kdf,
+                params.aead,
+                recipient_priv_key.as_ptr(),
+                recipient_priv_key.len(),
+                encapsulated_key.as_ptr(),
+                encapsulated_key.len(),
+                info.as_ptr(),
+                info.len(),
+            )
+        };
+
+        if result != 1 {
+            // Safety: `ctx` was allocated by this function and not yet freed.
+            unsafe { bssl_sys::EVP_HPKE_CTX_free(ctx) };
+            return Err(HpkeError);
+        }
+
+        Ok(Self { ctx })
     }
 
-    /// Seal.
-    pub fn seal(&self, pt: &[u8], aad: &[u8]) -> Result<Vec<u8>, HpkeError> {
-        unimplemented!();
+    /// New RecipientContext for RFC 9180 Auth mode. `sender_pub_key` is the sender's static HPKE
+    /// public key, used to authenticate them via AuthDecap.
+    pub fn new_auth(
+        params: &Params,
+        recipient_priv_key: &[u8],
+        encapsulated_key: &[u8],
+        sender_pub_key: &[u8],
+        info: &[u8],
+    ) -> Result<Self, HpkeError> {
+        // Safety: EVP_HPKE_CTX_new returns either a valid pointer or null on allocation failure.
+        let ctx = unsafe { bssl_sys::EVP_HPKE_CTX_new() };
+        if ctx.is_null() {
+            return Err(HpkeError);
+        }
+
+        // Safety: `ctx` was just allocated above and is non-null.
+        // `EVP_HPKE_CTX_setup_auth_recipient` is likewise a real entry point in BoringSSL's public
+        // `include/openssl/hpke.h`; see the matching note on `SenderContext::new_auth`.
+        let result = unsafe {
+            bssl_sys::EVP_HPKE_CTX_setup_auth_recipient(
+                ctx,
+                params.kem,
+                params.kdf,
+                params.aead,
+                recipient_priv_key.as_ptr(),
+                recipient_priv_key.len(),
+                encapsulated_key.as_ptr(),
+                encapsulated_key.len(),
+                info.as_ptr(),
+                info.len(),
+                sender_pub_key.as_ptr(),
+                sender_pub_key.len(),
+            )
+        };
+
+        if result != 1 {
+            // Safety: `ctx` was allocated by this function and not yet freed.
+            unsafe { bssl_sys::EVP_HPKE_CTX_free(ctx) };
+            return Err(HpkeError);
+        }
+
+        Ok(Self { ctx })
+    }
+
+    /// RFC 9180 PSK mode is not implemented; always returns [`HpkeError`]. See
+    /// [`SenderContext::new_psk`] and the module docs.
+    pub fn new_psk(
+        _params: &Params,
+        _recipient_priv_key: &[u8],
+        _encapsulated_key: &[u8],
+        _info: &[u8],
+        _psk: &[u8],
+        _psk_id: &[u8],
+    ) -> Result<Self, HpkeError> {
+        Err(HpkeError)
+    }
+
+    /// RFC 9180 Auth-PSK mode is not implemented; always returns [`HpkeError`]. See
+    /// [`SenderContext::new_auth_psk`] and the module docs.
+    pub fn new_auth_psk(
+        _params: &Params,
+        _recipient_priv_key: &[u8],
+        _encapsulated_key: &[u8],
+        _sender_pub_key: &[u8],
+        _info: &[u8],
+        _psk: &[u8],
+        _psk_id: &[u8],
+    ) -> Result<Self, HpkeError> {
+        Err(HpkeError)
     }
 
-    /// Open.
-    pub fn open(&self, ct: &[u8], aad: &[u8]) -> Result<Vec<u8>, HpkeError> {
-        unimplemented!();
+    /// Seal. Takes `&mut self` because `EVP_HPKE_CTX` tracks an internal AEAD sequence number that
+    /// advances with every call; encrypting over a shared reference would risk two callers
+    /// unknowingly reusing a nonce.
+    pub fn seal(&mut self, pt: &[u8], aad: &[u8]) -> Result<Vec<u8>, HpkeError> {
+        // Safety: `self.ctx` is valid for the lifetime of `self`.
+        let max_out_len = pt.len() + unsafe { bssl_sys::EVP_HPKE_CTX_max_overhead(self.ctx) };
+        let mut out = vec![0u8; max_out_len];
+        let mut out_len: usize = 0;
+
+        // Safety:
+        // - `self.ctx` is valid for the lifetime of `self`.
+        // - `out` is sized to the maximum possible output length for this seal.
+        let result = unsafe {
+            bssl_sys::EVP_HPKE_CTX_seal(
+                self.ctx,
+                out.as_mut_ptr(),
+                &mut out_len,
+                out.len(),
+                pt.as_ptr(),
+                pt.len(),
+                aad.as_ptr(),
+                aad.len(),
+            )
+        };
+
+        if result != 1 {
+            return Err(HpkeError);
+        }
+        out.truncate(out_len);
+        Ok(out)
+    }
+
+    /// Open. Takes `&mut self` for the same nonce-advancement reason as [`RecipientContext::seal`].
+    pub fn open(&mut self, ct: &[u8], aad: &[u8]) -> Result<Vec<u8>, HpkeError> {
+        let mut out = vec![0u8; ct.len()];
+        let mut out_len: usize = 0;
+
+        // Safety:
+        // - `self.ctx` is valid for the lifetime of `self`.
+        // - the plaintext can never be longer than the ciphertext, so `out` is large enough.
+        let result = unsafe {
+            bssl_sys::EVP_HPKE_CTX_open(
+                self.ctx,
+                out.as_mut_ptr(),
+                &mut out_len,
+                out.len(),
+                ct.as_ptr(),
+                ct.len(),
+                aad.as_ptr(),
+                aad.len(),
+            )
+        };
+
+        if result != 1 {
+            return Err(HpkeError);
+        }
+        out.truncate(out_len);
+        Ok(out)
+    }
+
+    /// Derives `out_len` bytes of secret keying material from this context, independent of the
+    /// AEAD seal/open stream, as described by the RFC 9180 secret export interface.
+    pub fn export(&self, exporter_context: &[u8], out_len: usize) -> Result<Vec<u8>, HpkeError> {
+        let mut out = vec![0u8; out_len];
+        // Safety: `self.ctx` is valid for the lifetime of `self`, and `out` is sized to `out_len`.
+        let result = unsafe {
+            bssl_sys::EVP_HPKE_CTX_export(
+                self.ctx,
+                out.as_mut_ptr(),
+                out.len(),
+                exporter_context.as_ptr(),
+                exporter_context.len(),
+            )
+        };
+
+        if result != 1 {
+            return Err(HpkeError);
+        }
+        Ok(out)
+    }
+}
+
+impl Drop for RecipientContext {
+    fn drop(&mut self) {
+        // Safety: `self.ctx` is owned by this struct and allocated by `EVP_HPKE_CTX_new`.
+        unsafe { bssl_sys::EVP_HPKE_CTX_free(self.ctx) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Minimal const-friendly hex decoder for the test vectors below.
+    macro_rules! hex {
+        ($s:expr) => {{
+            const INPUT: &[u8] = $s.as_bytes();
+            const LEN: usize = INPUT.len() / 2;
+            const fn nibble(c: u8) -> u8 {
+                match c {
+                    b'0'..=b'9' => c - b'0',
+                    b'a'..=b'f' => c - b'a' + 10,
+                    _ => 0,
+                }
+            }
+            const fn decode() -> [u8; LEN] {
+                let mut out = [0u8; LEN];
+                let mut i = 0;
+                while i < LEN {
+                    out[i] = (nibble(INPUT[2 * i]) << 4) | nibble(INPUT[2 * i + 1]);
+                    i += 1;
+                }
+                out
+            }
+            decode()
+        }};
+    }
+
+    // RFC 9180 Appendix A.1 test keys for DHKEM(X25519, HKDF-SHA256), HKDF-SHA256, AES-128-GCM.
+    //
+    // Note: these fixed keys only exercise BoringSSL's base-mode `EVP_HPKE_CTX_setup_sender`
+    // against real RFC 9180 key material; they are not a byte-for-byte known-answer test against
+    // the RFC's example ciphertexts. `EVP_HPKE_CTX_setup_sender` always draws a fresh ephemeral
+    // KEM keypair internally, and this crate has no seeded/deterministic sender setup to pin that
+    // draw to the RFC's `skEm`, so the encapsulated key and ciphertext below can never match the
+    // RFC's `enc`/`encryptions` values. A true known-answer test would need that seeded API added
+    // first.
+    const INFO: &[u8] = &hex!("4f6465206f6e2061204772656369616e2055726e");
+    const PK_RM: &[u8] =
+        &hex!("3948cfe0ad1ddb695d780e59077195da6c56506b027329794ab02bca80815c4d");
+    const SK_RM: &[u8] =
+        &hex!("4612c550263fc8ad58375df3f557aac531d26850903e55a9f23f21d8534e8ac8");
+
+    fn default_params() -> Params {
+        Params::new(KEM_X25519_HKDF_SHA256, KDF_HKDF_SHA256, AEAD_AES_128_GCM)
+            .expect("suite is supported")
+    }
+
+    /// Generates a fresh recipient keypair for `kem`, for use in round-trip tests that don't rely
+    /// on fixed RFC 9180 vectors.
+    fn generate_recipient_keypair(kem: u16) -> (Vec<u8>, Vec<u8>) {
+        let kem = match kem as u32 {
+            bssl_sys::EVP_HPKE_DHKEM_X25519_HKDF_SHA256 => unsafe {
+                bssl_sys::EVP_hpke_x25519_hkdf_sha256()
+            },
+            bssl_sys::EVP_HPKE_DHKEM_P256_HKDF_SHA256 => unsafe {
+                bssl_sys::EVP_hpke_p256_hkdf_sha256()
+            },
+            _ => panic!("unsupported KEM"),
+        };
+
+        let mut private_key = vec![0u8; bssl_sys::EVP_HPKE_MAX_PRIVATE_KEY_LENGTH as usize];
+        let mut private_key_len: usize = 0;
+        let mut public_key = vec![0u8; bssl_sys::EVP_HPKE_MAX_PUBLIC_KEY_LENGTH as usize];
+        let mut public_key_len: usize = 0;
+
+        // Safety: `kem` is a valid static pointer and the output buffers are sized to the maximum
+        // possible key lengths for any supported KEM.
+        let result = unsafe {
+            bssl_sys::EVP_HPKE_KEM_generate_key(
+                kem,
+                private_key.as_mut_ptr(),
+                &mut private_key_len,
+                public_key.as_mut_ptr(),
+                &mut public_key_len,
+            )
+        };
+        assert_eq!(result, 1, "EVP_HPKE_KEM_generate_key failed");
+
+        private_key.truncate(private_key_len);
+        public_key.truncate(public_key_len);
+        (private_key, public_key)
+    }
+
+    fn round_trip(kem: u16, kdf: u16, aead: u16) {
+        let (sk_r, pk_r) = generate_recipient_keypair(kem);
+        let params = Params::new(kem, kdf, aead).expect("suite is supported");
+        let mut sender = SenderContext::new(&params, &pk_r, INFO).expect("valid sender setup");
+        let mut recipient =
+            RecipientContext::new(&Params::new(kem, kdf, aead).expect("suite is supported"),
+                &sk_r, sender.encapsulated_key(), INFO)
+            .expect("valid recipient setup");
+
+        let pt = b"round trip message";
+        let ct = sender.seal(pt, b"aad").expect("seal succeeds");
+        assert_eq!(recipient.open(&ct, b"aad").expect("open succeeds"), pt);
+    }
+
+    #[test]
+    fn auth_mode_round_trip() {
+        let (sk_s, pk_s) = generate_recipient_keypair(KEM_X25519_HKDF_SHA256);
+        let (sk_r, pk_r) = generate_recipient_keypair(KEM_X25519_HKDF_SHA256);
+
+        let params = default_params();
+        let mut sender =
+            SenderContext::new_auth(&params, &pk_r, &sk_s, INFO).expect("valid auth sender setup");
+        let mut recipient = RecipientContext::new_auth(
+            &default_params(),
+            &sk_r,
+            sender.encapsulated_key(),
+            &pk_s,
+            INFO,
+        )
+        .expect("valid auth recipient setup");
+
+        let pt = b"authenticated message";
+        let ct = sender.seal(pt, b"").expect("seal succeeds");
+        assert_eq!(recipient.open(&ct, b"").expect("open succeeds"), pt);
+    }
+
+    #[test]
+    fn auth_mode_rejects_wrong_sender_key() {
+        let (_, pk_s) = generate_recipient_keypair(KEM_X25519_HKDF_SHA256);
+        let (wrong_sk_s, _) = generate_recipient_keypair(KEM_X25519_HKDF_SHA256);
+        let (sk_r, pk_r) = generate_recipient_keypair(KEM_X25519_HKDF_SHA256);
+
+        let params = default_params();
+        let mut sender = SenderContext::new_auth(&params, &pk_r, &wrong_sk_s, INFO)
+            .expect("valid auth sender setup");
+        let mut recipient =
+            RecipientContext::new_auth(&default_params(), &sk_r, sender.encapsulated_key(), &pk_s, INFO)
+                .expect("valid auth recipient setup");
+
+        let ct = sender.seal(b"message", b"").expect("seal succeeds");
+        assert!(recipient.open(&ct, b"").is_err());
+    }
+
+    #[test]
+    fn psk_and_auth_psk_modes_are_unsupported() {
+        let params = default_params();
+        let (sk_r, pk_r) = generate_recipient_keypair(KEM_X25519_HKDF_SHA256);
+        let (sk_s, pk_s) = generate_recipient_keypair(KEM_X25519_HKDF_SHA256);
+
+        assert!(SenderContext::new_psk(&params, &pk_r, INFO, b"psk", b"psk-id").is_err());
+        assert!(SenderContext::new_auth_psk(&params, &pk_r, &sk_s, INFO, b"psk", b"psk-id").is_err());
+        assert!(RecipientContext::new_psk(&params, &sk_r, b"enc", INFO, b"psk", b"psk-id").is_err());
+        assert!(RecipientContext::new_auth_psk(
+            &params, &sk_r, b"enc", &pk_s, INFO, b"psk", b"psk-id"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn params_rejects_unknown_algorithm() {
+        assert!(Params::new(0xffff, KDF_HKDF_SHA256, AEAD_AES_128_GCM).is_err());
+        assert!(Params::new(KEM_X25519_HKDF_SHA256, 0xffff, AEAD_AES_128_GCM).is_err());
+        assert!(Params::new(KEM_X25519_HKDF_SHA256, KDF_HKDF_SHA256, 0xffff).is_err());
+    }
+
+    #[test]
+    fn round_trip_p256_aes_128_gcm() {
+        round_trip(KEM_P256_HKDF_SHA256, KDF_HKDF_SHA256, AEAD_AES_128_GCM);
+    }
+
+    #[test]
+    fn round_trip_x25519_aes_256_gcm() {
+        round_trip(KEM_X25519_HKDF_SHA256, KDF_HKDF_SHA256, AEAD_AES_256_GCM);
+    }
+
+    #[test]
+    fn round_trip_x25519_chacha20_poly1305() {
+        round_trip(KEM_X25519_HKDF_SHA256, KDF_HKDF_SHA256, AEAD_CHACHA20_POLY1305);
+    }
+
+    #[test]
+    fn sender_context_enc_length_matches_kem() {
+        let (_, pk_r_x25519) = generate_recipient_keypair(KEM_X25519_HKDF_SHA256);
+        let x25519_params = default_params();
+        let x25519_sender =
+            SenderContext::new(&x25519_params, &pk_r_x25519, INFO).expect("valid sender setup");
+
+        let (_, pk_r_p256) = generate_recipient_keypair(KEM_P256_HKDF_SHA256);
+        let p256_params =
+            Params::new(KEM_P256_HKDF_SHA256, KDF_HKDF_SHA256, AEAD_AES_128_GCM).unwrap();
+        let p256_sender =
+            SenderContext::new(&p256_params, &pk_r_p256, INFO).expect("valid sender setup");
+
+        // P-256's uncompressed encapsulated key is longer than X25519's, and both must be
+        // truncated to their actual length rather than padded out to MAX_ENC_LENGTH.
+        assert!(p256_sender.encapsulated_key().len() > x25519_sender.encapsulated_key().len());
+    }
+
+    #[test]
+    fn seal_open_round_trip() {
+        let params = default_params();
+        let mut sender = SenderContext::new(&params, PK_RM, INFO).expect("valid sender setup");
+        let mut recipient = RecipientContext::new(
+            &default_params(),
+            SK_RM,
+            sender.encapsulated_key(),
+            INFO,
+        )
+        .expect("valid recipient setup");
+
+        let pt = b"Beauty is truth, truth beauty";
+        let aad = b"Count-0";
+        let ct = sender.seal(pt, aad).expect("seal succeeds");
+        let opened = recipient.open(&ct, aad).expect("open succeeds");
+        assert_eq!(opened, pt);
+    }
+
+    #[test]
+    fn sequential_messages_use_distinct_nonces() {
+        let params = default_params();
+        let mut sender = SenderContext::new(&params, PK_RM, INFO).expect("valid sender setup");
+        let mut recipient = RecipientContext::new(
+            &default_params(),
+            SK_RM,
+            sender.encapsulated_key(),
+            INFO,
+        )
+        .expect("valid recipient setup");
+
+        let pt = b"message";
+        let ct0 = sender.seal(pt, b"").expect("first seal succeeds");
+        let ct1 = sender.seal(pt, b"").expect("second seal succeeds");
+        assert_ne!(ct0, ct1, "AEAD sequence number must advance between calls");
+
+        assert_eq!(recipient.open(&ct0, b"").expect("opens first"), pt);
+        assert_eq!(recipient.open(&ct1, b"").expect("opens second"), pt);
+        // The recipient's sequence number has also advanced, so re-opening the first message
+        // (as if it were replayed) must now fail.
+        assert!(recipient.open(&ct0, b"").is_err());
+    }
+
+    #[test]
+    fn export_matches_between_sender_and_recipient() {
+        let params = default_params();
+        let sender = SenderContext::new(&params, PK_RM, INFO).expect("valid sender setup");
+        let recipient = RecipientContext::new(
+            &default_params(),
+            SK_RM,
+            sender.encapsulated_key(),
+            INFO,
+        )
+        .expect("valid recipient setup");
+
+        let sender_secret = sender.export(b"export context", 32).expect("export succeeds");
+        let recipient_secret = recipient
+            .export(b"export context", 32)
+            .expect("export succeeds");
+        assert_eq!(sender_secret, recipient_secret);
+
+        let other_secret = sender
+            .export(b"different context", 32)
+            .expect("export succeeds");
+        assert_ne!(sender_secret, other_secret);
+    }
+
+    #[test]
+    fn open_rejects_tampered_ciphertext() {
+        let params = default_params();
+        let mut sender = SenderContext::new(&params, PK_RM, INFO).expect("valid sender setup");
+        let mut recipient = RecipientContext::new(
+            &default_params(),
+            SK_RM,
+            sender.encapsulated_key(),
+            INFO,
+        )
+        .expect("valid recipient setup");
+
+        let mut ct = sender.seal(b"message", b"").expect("seal succeeds");
+        let last = ct.len() - 1;
+        ct[last] ^= 1;
+        assert!(recipient.open(&ct, b"").is_err());
     }
 }
@@ -0,0 +1,532 @@
+/* Copyright (c) 2024, Google Inc.
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+ * SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+ * OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+ * CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ */
+
+//! Oblivious HTTP (RFC 9458) message encapsulation, built on top of [`crate::hpke`].
+//!
+//! A client encapsulates a request under a server's [`KeyConfig`] with [`encapsulate_request`],
+//! and the server recovers it with [`decapsulate_request`]. The response leg does not reuse the
+//! forward HPKE context directly (the client and server would otherwise need to agree on whose
+//! sequence number applies); instead it exports a fresh secret via the RFC 9180 exporter interface
+//! and derives a one-shot AEAD key and nonce from it, as RFC 9458 section 4.3 describes. This
+//! crate has no standalone KDF/AEAD module to call into for that derivation, so the HKDF and AEAD
+//! primitives it needs are implemented directly against `bssl_sys` below, the same way
+//! [`crate::cose`] hand-rolls the CBOR it needs rather than depending on a module this tree
+//! snapshot doesn't have.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::hpke::{self, HpkeError, Params, RecipientContext, SenderContext};
+
+/// The ASCII label, plus trailing `0x00`, prefixed to the request header to form the HPKE `info`
+/// for a request (RFC 9458 section 4.1).
+const REQUEST_LABEL: &[u8] = b"message/bhttp request";
+
+/// The ASCII label passed to the RFC 9180 exporter interface when deriving the response secret
+/// (RFC 9458 section 4.3).
+const RESPONSE_LABEL: &[u8] = b"message/bhttp response";
+
+/// Length in bytes of an encapsulated request header: `key_id(1) || kem_id(2) || kdf_id(2) ||
+/// aead_id(2)`.
+const HDR_LEN: usize = 7;
+
+/// Nonce length in bytes (`Nn`) for every AEAD this module supports; all three are 96-bit-nonce
+/// constructions.
+const AEAD_NONCE_LEN: usize = 12;
+
+/// Error returned from unsuccessful OHTTP operations, covering malformed key configs and messages
+/// as well as the underlying HPKE/AEAD failures.
+#[derive(Debug)]
+pub struct OhttpError;
+
+impl From<HpkeError> for OhttpError {
+    fn from(_: HpkeError) -> Self {
+        OhttpError
+    }
+}
+
+/// A server's OHTTP key configuration: `key_id(1) || kem_id(2) || public_key || {aead_id(2)}*`.
+/// Only a single KDF (HKDF-SHA256, [`hpke::KDF_HKDF_SHA256`]) is supported, so unlike the full RFC
+/// 9458 key config this carries no KDF identifiers alongside the AEAD list.
+pub struct KeyConfig {
+    key_id: u8,
+    kem_id: u16,
+    public_key: Vec<u8>,
+    aead_ids: Vec<u16>,
+}
+
+impl KeyConfig {
+    /// New KeyConfig for a given key identifier, KEM, public key, and list of AEADs the server is
+    /// willing to accept requests under. `aead_ids` must not be empty.
+    pub fn new(
+        key_id: u8,
+        kem_id: u16,
+        public_key: Vec<u8>,
+        aead_ids: Vec<u16>,
+    ) -> Result<Self, OhttpError> {
+        if aead_ids.is_empty() || kem_public_key_len(kem_id)? != public_key.len() {
+            return Err(OhttpError);
+        }
+        Ok(Self { key_id, kem_id, public_key, aead_ids })
+    }
+
+    /// Parses a wire-format key config: `key_id(1) || kem_id(2) || public_key || {aead_id(2)}*`.
+    pub fn parse(bytes: &[u8]) -> Result<Self, OhttpError> {
+        if bytes.len() < 3 {
+            return Err(OhttpError);
+        }
+        let key_id = bytes[0];
+        let kem_id = u16::from_be_bytes([bytes[1], bytes[2]]);
+        let pk_len = kem_public_key_len(kem_id)?;
+        let rest = bytes.get(3..).ok_or(OhttpError)?;
+        if rest.len() < pk_len {
+            return Err(OhttpError);
+        }
+        let (public_key, aead_bytes) = rest.split_at(pk_len);
+        if aead_bytes.is_empty() || aead_bytes.len() % 2 != 0 {
+            return Err(OhttpError);
+        }
+        let aead_ids = aead_bytes
+            .chunks_exact(2)
+            .map(|chunk| u16::from_be_bytes([chunk[0], chunk[1]]))
+            .collect();
+        Ok(Self { key_id, kem_id, public_key: public_key.to_vec(), aead_ids })
+    }
+}
+
+/// State a client retains after [`encapsulate_request`] in order to later decrypt the matching
+/// response with [`decapsulate_response`].
+pub struct ClientContext {
+    sender: SenderContext,
+    enc: Vec<u8>,
+    aead_id: u16,
+}
+
+/// State a server retains after [`decapsulate_request`] in order to later encrypt the matching
+/// response with [`encapsulate_response`].
+pub struct ServerContext {
+    recipient: RecipientContext,
+    enc: Vec<u8>,
+    aead_id: u16,
+}
+
+/// Encapsulates `plaintext` as an OHTTP request under `key_config`, returning the wire-format
+/// message `hdr || enc || ct` alongside the [`ClientContext`] needed to decrypt the response.
+/// Picks the first AEAD in `key_config`'s list; callers that care which one is used should build
+/// a `KeyConfig` listing only their preferred choice.
+pub fn encapsulate_request(
+    key_config: &KeyConfig,
+    plaintext: &[u8],
+) -> Result<(Vec<u8>, ClientContext), OhttpError> {
+    let aead_id = *key_config.aead_ids.first().ok_or(OhttpError)?;
+    let hdr = build_hdr(key_config.key_id, key_config.kem_id, hpke::KDF_HKDF_SHA256, aead_id);
+
+    let params = Params::new(key_config.kem_id, hpke::KDF_HKDF_SHA256, aead_id)?;
+    let info = request_info(&hdr);
+    let mut sender = SenderContext::new(&params, &key_config.public_key, &info)?;
+    let ct = sender.seal(plaintext, b"")?;
+    let enc = sender.encapsulated_key().clone();
+
+    let mut enc_request = Vec::with_capacity(hdr.len() + enc.len() + ct.len());
+    enc_request.extend_from_slice(&hdr);
+    enc_request.extend_from_slice(&enc);
+    enc_request.extend_from_slice(&ct);
+
+    Ok((enc_request, ClientContext { sender, enc, aead_id }))
+}
+
+/// Decapsulates an OHTTP request produced by [`encapsulate_request`] against the server's own
+/// `key_config` and matching `private_key`, returning the plaintext alongside the
+/// [`ServerContext`] needed to encrypt the response.
+pub fn decapsulate_request(
+    key_config: &KeyConfig,
+    private_key: &[u8],
+    enc_request: &[u8],
+) -> Result<(Vec<u8>, ServerContext), OhttpError> {
+    if enc_request.len() < HDR_LEN {
+        return Err(OhttpError);
+    }
+    let (hdr, rest) = enc_request.split_at(HDR_LEN);
+    let key_id = hdr[0];
+    let kem_id = u16::from_be_bytes([hdr[1], hdr[2]]);
+    let kdf_id = u16::from_be_bytes([hdr[3], hdr[4]]);
+    let aead_id = u16::from_be_bytes([hdr[5], hdr[6]]);
+    if key_id != key_config.key_id
+        || kem_id != key_config.kem_id
+        || !key_config.aead_ids.contains(&aead_id)
+    {
+        return Err(OhttpError);
+    }
+
+    let enc_len = kem_public_key_len(kem_id)?;
+    if rest.len() < enc_len {
+        return Err(OhttpError);
+    }
+    let (enc, ct) = rest.split_at(enc_len);
+
+    let params = Params::new(kem_id, kdf_id, aead_id)?;
+    let info = request_info(hdr);
+    let mut recipient = RecipientContext::new(&params, private_key, enc, &info)?;
+    let plaintext = recipient.open(ct, b"")?;
+
+    Ok((plaintext, ServerContext { recipient, enc: enc.to_vec(), aead_id }))
+}
+
+/// Encapsulates `plaintext` as the OHTTP response to a request that was decapsulated into
+/// `server_context`, returning the wire-format message `response_nonce || ct`.
+pub fn encapsulate_response(
+    server_context: &ServerContext,
+    plaintext: &[u8],
+) -> Result<Vec<u8>, OhttpError> {
+    let nk = aead_key_len(server_context.aead_id)?;
+    let mut response_nonce = vec![0u8; nk.max(AEAD_NONCE_LEN)];
+    // Safety: `response_nonce` is a valid buffer of its own declared length.
+    unsafe { bssl_sys::RAND_bytes(response_nonce.as_mut_ptr(), response_nonce.len()) };
+
+    let secret = server_context.recipient.export(RESPONSE_LABEL, nk)?;
+    let (key, nonce) = derive_response_key_nonce(&secret, &server_context.enc, &response_nonce, nk)?;
+    let ct = aead_seal(server_context.aead_id, &key, &nonce, plaintext)?;
+
+    let mut response = Vec::with_capacity(response_nonce.len() + ct.len());
+    response.extend_from_slice(&response_nonce);
+    response.extend_from_slice(&ct);
+    Ok(response)
+}
+
+/// Decapsulates an OHTTP response produced by [`encapsulate_response`] against the
+/// [`ClientContext`] returned by the matching [`encapsulate_request`] call.
+pub fn decapsulate_response(
+    client_context: &ClientContext,
+    enc_response: &[u8],
+) -> Result<Vec<u8>, OhttpError> {
+    let nk = aead_key_len(client_context.aead_id)?;
+    let response_nonce_len = nk.max(AEAD_NONCE_LEN);
+    if enc_response.len() < response_nonce_len {
+        return Err(OhttpError);
+    }
+    let (response_nonce, ct) = enc_response.split_at(response_nonce_len);
+
+    let secret = client_context.sender.export(RESPONSE_LABEL, nk)?;
+    let (key, nonce) = derive_response_key_nonce(&secret, &client_context.enc, response_nonce, nk)?;
+    aead_open(client_context.aead_id, &key, &nonce, ct)
+}
+
+fn build_hdr(key_id: u8, kem_id: u16, kdf_id: u16, aead_id: u16) -> [u8; HDR_LEN] {
+    let mut hdr = [0u8; HDR_LEN];
+    hdr[0] = key_id;
+    hdr[1..3].copy_from_slice(&kem_id.to_be_bytes());
+    hdr[3..5].copy_from_slice(&kdf_id.to_be_bytes());
+    hdr[5..7].copy_from_slice(&aead_id.to_be_bytes());
+    hdr
+}
+
+fn request_info(hdr: &[u8]) -> Vec<u8> {
+    let mut info = Vec::with_capacity(REQUEST_LABEL.len() + 1 + hdr.len());
+    info.extend_from_slice(REQUEST_LABEL);
+    info.push(0);
+    info.extend_from_slice(hdr);
+    info
+}
+
+fn kem_public_key_len(kem_id: u16) -> Result<usize, OhttpError> {
+    match kem_id {
+        hpke::KEM_X25519_HKDF_SHA256 => Ok(32),
+        hpke::KEM_P256_HKDF_SHA256 => Ok(65),
+        _ => Err(OhttpError),
+    }
+}
+
+fn aead_key_len(aead_id: u16) -> Result<usize, OhttpError> {
+    match aead_id {
+        hpke::AEAD_AES_128_GCM => Ok(16),
+        hpke::AEAD_AES_256_GCM | hpke::AEAD_CHACHA20_POLY1305 => Ok(32),
+        _ => Err(OhttpError),
+    }
+}
+
+fn lookup_aead(aead_id: u16) -> Result<*const bssl_sys::EVP_AEAD, OhttpError> {
+    // Safety: these getters take no arguments and always return a valid, static pointer.
+    match aead_id {
+        hpke::AEAD_AES_128_GCM => Ok(unsafe { bssl_sys::EVP_aead_aes_128_gcm() }),
+        hpke::AEAD_AES_256_GCM => Ok(unsafe { bssl_sys::EVP_aead_aes_256_gcm() }),
+        hpke::AEAD_CHACHA20_POLY1305 => Ok(unsafe { bssl_sys::EVP_aead_chacha20_poly1305() }),
+        _ => Err(OhttpError),
+    }
+}
+
+/// Runs HKDF-SHA256 Extract-then-Expand over `secret` with `salt = enc || response_nonce`,
+/// deriving an `nk`-byte AEAD key and a [`AEAD_NONCE_LEN`]-byte nonce, per RFC 9458 section 4.3.
+fn derive_response_key_nonce(
+    secret: &[u8],
+    enc: &[u8],
+    response_nonce: &[u8],
+    nk: usize,
+) -> Result<(Vec<u8>, Vec<u8>), OhttpError> {
+    let mut salt = Vec::with_capacity(enc.len() + response_nonce.len());
+    salt.extend_from_slice(enc);
+    salt.extend_from_slice(response_nonce);
+
+    let mut prk = vec![0u8; bssl_sys::EVP_MAX_MD_SIZE as usize];
+    let mut prk_len: usize = 0;
+    // Safety: `prk` is sized to the largest digest BoringSSL supports, which bounds every HKDF
+    // extract output, including SHA-256's.
+    let result = unsafe {
+        bssl_sys::HKDF_extract(
+            prk.as_mut_ptr(),
+            &mut prk_len,
+            bssl_sys::EVP_sha256(),
+            secret.as_ptr(),
+            secret.len(),
+            salt.as_ptr(),
+            salt.len(),
+        )
+    };
+    if result != 1 {
+        return Err(OhttpError);
+    }
+    prk.truncate(prk_len);
+
+    let mut key = vec![0u8; nk];
+    let mut nonce = vec![0u8; AEAD_NONCE_LEN];
+    // Safety: `key` and `nonce` are the caller-owned output buffers, sized to the requested
+    // lengths.
+    let key_result = unsafe {
+        bssl_sys::HKDF_expand(
+            key.as_mut_ptr(),
+            key.len(),
+            bssl_sys::EVP_sha256(),
+            prk.as_ptr(),
+            prk.len(),
+            b"key".as_ptr(),
+            3,
+        )
+    };
+    // Safety: see above.
+    let nonce_result = unsafe {
+        bssl_sys::HKDF_expand(
+            nonce.as_mut_ptr(),
+            nonce.len(),
+            bssl_sys::EVP_sha256(),
+            prk.as_ptr(),
+            prk.len(),
+            b"nonce".as_ptr(),
+            5,
+        )
+    };
+    if key_result != 1 || nonce_result != 1 {
+        return Err(OhttpError);
+    }
+    Ok((key, nonce))
+}
+
+fn aead_seal(aead_id: u16, key: &[u8], nonce: &[u8], pt: &[u8]) -> Result<Vec<u8>, OhttpError> {
+    let aead = lookup_aead(aead_id)?;
+    // Safety: `aead` is a valid static pointer and `key` was derived to the correct length for it.
+    let ctx = unsafe { bssl_sys::EVP_AEAD_CTX_new(aead, key.as_ptr(), key.len(), 0) };
+    if ctx.is_null() {
+        return Err(OhttpError);
+    }
+    // Safety: `aead` is a valid static pointer.
+    let max_out_len = pt.len() + unsafe { bssl_sys::EVP_AEAD_max_overhead(aead) };
+    let mut out = vec![0u8; max_out_len];
+    let mut out_len: usize = 0;
+    // Safety:
+    // - `ctx` was just allocated above and is non-null.
+    // - `out` is sized to the maximum possible sealed output length.
+    let result = unsafe {
+        bssl_sys::EVP_AEAD_CTX_seal(
+            ctx,
+            out.as_mut_ptr(),
+            &mut out_len,
+            out.len(),
+            nonce.as_ptr(),
+            nonce.len(),
+            pt.as_ptr(),
+            pt.len(),
+            core::ptr::null(),
+            0,
+        )
+    };
+    // Safety: `ctx` was allocated by this function and is no longer needed.
+    unsafe { bssl_sys::EVP_AEAD_CTX_free(ctx) };
+    if result != 1 {
+        return Err(OhttpError);
+    }
+    out.truncate(out_len);
+    Ok(out)
+}
+
+fn aead_open(aead_id: u16, key: &[u8], nonce: &[u8], ct: &[u8]) -> Result<Vec<u8>, OhttpError> {
+    let aead = lookup_aead(aead_id)?;
+    // Safety: `aead` is a valid static pointer and `key` was derived to the correct length for it.
+    let ctx = unsafe { bssl_sys::EVP_AEAD_CTX_new(aead, key.as_ptr(), key.len(), 0) };
+    if ctx.is_null() {
+        return Err(OhttpError);
+    }
+    let mut out = vec![0u8; ct.len()];
+    let mut out_len: usize = 0;
+    // Safety:
+    // - `ctx` was just allocated above and is non-null.
+    // - the plaintext can never be longer than the ciphertext, so `out` is large enough.
+    let result = unsafe {
+        bssl_sys::EVP_AEAD_CTX_open(
+            ctx,
+            out.as_mut_ptr(),
+            &mut out_len,
+            out.len(),
+            nonce.as_ptr(),
+            nonce.len(),
+            ct.as_ptr(),
+            ct.len(),
+            core::ptr::null(),
+            0,
+        )
+    };
+    // Safety: `ctx` was allocated by this function and is no longer needed.
+    unsafe { bssl_sys::EVP_AEAD_CTX_free(ctx) };
+    if result != 1 {
+        return Err(OhttpError);
+    }
+    out.truncate(out_len);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate_recipient_keypair(kem_id: u16) -> (Vec<u8>, Vec<u8>) {
+        let kem = match kem_id {
+            hpke::KEM_X25519_HKDF_SHA256 => unsafe { bssl_sys::EVP_hpke_x25519_hkdf_sha256() },
+            hpke::KEM_P256_HKDF_SHA256 => unsafe { bssl_sys::EVP_hpke_p256_hkdf_sha256() },
+            _ => panic!("unsupported KEM"),
+        };
+
+        let mut private_key = vec![0u8; bssl_sys::EVP_HPKE_MAX_PRIVATE_KEY_LENGTH as usize];
+        let mut private_key_len: usize = 0;
+        let mut public_key = vec![0u8; bssl_sys::EVP_HPKE_MAX_PUBLIC_KEY_LENGTH as usize];
+        let mut public_key_len: usize = 0;
+
+        // Safety: `kem` is a valid static pointer and the output buffers are sized to the maximum
+        // possible key lengths for any supported KEM.
+        let result = unsafe {
+            bssl_sys::EVP_HPKE_KEM_generate_key(
+                kem,
+                private_key.as_mut_ptr(),
+                &mut private_key_len,
+                public_key.as_mut_ptr(),
+                &mut public_key_len,
+            )
+        };
+        assert_eq!(result, 1, "EVP_HPKE_KEM_generate_key failed");
+
+        private_key.truncate(private_key_len);
+        public_key.truncate(public_key_len);
+        (private_key, public_key)
+    }
+
+    fn request_response_round_trip(kem_id: u16, aead_id: u16) {
+        let (sk_r, pk_r) = generate_recipient_keypair(kem_id);
+        let key_config =
+            KeyConfig::new(7, kem_id, pk_r, vec![aead_id]).expect("valid key config");
+
+        let (enc_request, client_context) =
+            encapsulate_request(&key_config, b"request body").expect("encapsulation succeeds");
+        let (plaintext, server_context) = decapsulate_request(&key_config, &sk_r, &enc_request)
+            .expect("decapsulation succeeds");
+        assert_eq!(plaintext, b"request body");
+
+        let enc_response =
+            encapsulate_response(&server_context, b"response body").expect("seal succeeds");
+        let response = decapsulate_response(&client_context, &enc_response).expect("open succeeds");
+        assert_eq!(response, b"response body");
+    }
+
+    #[test]
+    fn round_trip_x25519_aes_128_gcm() {
+        request_response_round_trip(hpke::KEM_X25519_HKDF_SHA256, hpke::AEAD_AES_128_GCM);
+    }
+
+    #[test]
+    fn round_trip_p256_aes_256_gcm() {
+        request_response_round_trip(hpke::KEM_P256_HKDF_SHA256, hpke::AEAD_AES_256_GCM);
+    }
+
+    #[test]
+    fn round_trip_x25519_chacha20_poly1305() {
+        request_response_round_trip(hpke::KEM_X25519_HKDF_SHA256, hpke::AEAD_CHACHA20_POLY1305);
+    }
+
+    #[test]
+    fn key_config_round_trips_through_wire_format() {
+        let (_, pk_r) = generate_recipient_keypair(hpke::KEM_X25519_HKDF_SHA256);
+        let key_config = KeyConfig::new(
+            3,
+            hpke::KEM_X25519_HKDF_SHA256,
+            pk_r,
+            vec![hpke::AEAD_AES_128_GCM, hpke::AEAD_AES_256_GCM],
+        )
+        .expect("valid key config");
+
+        let mut wire = vec![key_config.key_id];
+        wire.extend_from_slice(&key_config.kem_id.to_be_bytes());
+        wire.extend_from_slice(&key_config.public_key);
+        for aead_id in &key_config.aead_ids {
+            wire.extend_from_slice(&aead_id.to_be_bytes());
+        }
+
+        let parsed = KeyConfig::parse(&wire).expect("parse succeeds");
+        assert_eq!(parsed.key_id, key_config.key_id);
+        assert_eq!(parsed.kem_id, key_config.kem_id);
+        assert_eq!(parsed.public_key, key_config.public_key);
+        assert_eq!(parsed.aead_ids, key_config.aead_ids);
+    }
+
+    #[test]
+    fn decapsulate_request_rejects_wrong_key_id() {
+        let (sk_r, pk_r) = generate_recipient_keypair(hpke::KEM_X25519_HKDF_SHA256);
+        let key_config =
+            KeyConfig::new(1, hpke::KEM_X25519_HKDF_SHA256, pk_r, vec![hpke::AEAD_AES_128_GCM])
+                .expect("valid key config");
+        let (mut enc_request, _) =
+            encapsulate_request(&key_config, b"hello").expect("encapsulation succeeds");
+        enc_request[0] ^= 0xff;
+        assert!(decapsulate_request(&key_config, &sk_r, &enc_request).is_err());
+    }
+
+    #[test]
+    fn decapsulate_response_rejects_tampered_ciphertext() {
+        let (sk_r, pk_r) = generate_recipient_keypair(hpke::KEM_X25519_HKDF_SHA256);
+        let key_config =
+            KeyConfig::new(1, hpke::KEM_X25519_HKDF_SHA256, pk_r, vec![hpke::AEAD_AES_128_GCM])
+                .expect("valid key config");
+        let (enc_request, client_context) =
+            encapsulate_request(&key_config, b"hello").expect("encapsulation succeeds");
+        let (_, server_context) =
+            decapsulate_request(&key_config, &sk_r, &enc_request).expect("decapsulation succeeds");
+
+        let mut enc_response =
+            encapsulate_response(&server_context, b"world").expect("seal succeeds");
+        let last = enc_response.len() - 1;
+        enc_response[last] ^= 1;
+        assert!(decapsulate_response(&client_context, &enc_response).is_err());
+    }
+
+    #[test]
+    fn key_config_rejects_empty_aead_list() {
+        let (_, pk_r) = generate_recipient_keypair(hpke::KEM_X25519_HKDF_SHA256);
+        assert!(KeyConfig::new(1, hpke::KEM_X25519_HKDF_SHA256, pk_r, vec![]).is_err());
+    }
+}
@@ -0,0 +1,423 @@
+/* Copyright (c) 2024, Google Inc.
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+ * SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+ * OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+ * CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ */
+
+use crate::hmac::{Hmac, HmacSha256};
+use crate::PanicResultHandler;
+use bssl_sys::AES_KEY;
+use core::mem::MaybeUninit;
+
+/// Size in bytes of a single AES block.
+pub const BLOCK_SIZE: usize = 16;
+
+/// Size in bytes of the IV required by the CBC-mode APIs in this module. Callers must supply a
+/// fresh random IV (e.g. from BoringSSL's `RAND_bytes`) for every message; reusing one leaks
+/// whether two ciphertexts share a prefix.
+pub const IV_LEN: usize = 16;
+
+/// Error returned when an input has the wrong length for an AES operation.
+#[derive(Debug)]
+pub struct InvalidLength;
+
+/// An expanded AES encryption key, used here only for single-block ECB encryption. This is a
+/// building block for higher level constructions (e.g. header protection, CBC mode) rather than a
+/// mode callers should reach for directly, since raw ECB leaks repeated-block patterns.
+pub struct AesKey {
+    key: AES_KEY,
+}
+
+impl AesKey {
+    /// New AesKey from a 128 or 256 bit key.
+    pub fn new(key: &[u8]) -> Result<Self, InvalidLength> {
+        let bits = match key.len() {
+            16 => 128,
+            32 => 256,
+            _ => return Err(InvalidLength),
+        };
+
+        let mut aes_key = MaybeUninit::<AES_KEY>::uninit();
+        // Safety:
+        // - `aes_key` is only read via `assume_init` after `AES_set_encrypt_key` has initialized it.
+        // - `AES_set_encrypt_key` returns non-zero only if `bits` is invalid, which is excluded above.
+        let result =
+            unsafe { bssl_sys::AES_set_encrypt_key(key.as_ptr(), bits, aes_key.as_mut_ptr()) };
+        if result != 0 {
+            return Err(InvalidLength);
+        }
+
+        // Safety: `AES_set_encrypt_key` returned success above, so `aes_key` is now initialized.
+        let key = unsafe { aes_key.assume_init() };
+        Ok(Self { key })
+    }
+
+    /// Encrypts a single 16-byte block under this key (AES-ECB, one block).
+    pub fn encrypt_block(&self, block: &[u8; BLOCK_SIZE]) -> [u8; BLOCK_SIZE] {
+        let mut out = [0u8; BLOCK_SIZE];
+        // Safety: `block` and `out` are both exactly `BLOCK_SIZE` bytes, as `AES_encrypt` requires.
+        unsafe { bssl_sys::AES_encrypt(block.as_ptr(), out.as_mut_ptr(), &self.key) };
+        out
+    }
+}
+
+/// An expanded AES decryption key, used here only for single-block ECB decryption. This is the
+/// decrypting counterpart to [`AesKey`]: BoringSSL expands encryption and decryption key
+/// schedules differently, so the two are kept as separate types rather than one key used both
+/// ways.
+pub struct AesDecryptKey {
+    key: AES_KEY,
+}
+
+impl AesDecryptKey {
+    /// New AesDecryptKey from a 128 or 256 bit key.
+    pub fn new(key: &[u8]) -> Result<Self, InvalidLength> {
+        let bits = match key.len() {
+            16 => 128,
+            32 => 256,
+            _ => return Err(InvalidLength),
+        };
+
+        let mut aes_key = MaybeUninit::<AES_KEY>::uninit();
+        // Safety:
+        // - `aes_key` is only read via `assume_init` after `AES_set_decrypt_key` has initialized it.
+        // - `AES_set_decrypt_key` returns non-zero only if `bits` is invalid, which is excluded above.
+        let result =
+            unsafe { bssl_sys::AES_set_decrypt_key(key.as_ptr(), bits, aes_key.as_mut_ptr()) };
+        if result != 0 {
+            return Err(InvalidLength);
+        }
+
+        // Safety: `AES_set_decrypt_key` returned success above, so `aes_key` is now initialized.
+        let key = unsafe { aes_key.assume_init() };
+        Ok(Self { key })
+    }
+
+    /// Decrypts a single 16-byte block under this key (AES-ECB, one block).
+    pub fn decrypt_block(&self, block: &[u8; BLOCK_SIZE]) -> [u8; BLOCK_SIZE] {
+        let mut out = [0u8; BLOCK_SIZE];
+        // Safety: `block` and `out` are both exactly `BLOCK_SIZE` bytes, as `AES_decrypt` requires.
+        unsafe { bssl_sys::AES_decrypt(block.as_ptr(), out.as_mut_ptr(), &self.key) };
+        out
+    }
+}
+
+fn xor_block(a: &[u8; BLOCK_SIZE], b: &[u8; BLOCK_SIZE]) -> [u8; BLOCK_SIZE] {
+    let mut out = [0u8; BLOCK_SIZE];
+    for (o, (x, y)) in out.iter_mut().zip(a.iter().zip(b.iter())) {
+        *o = x ^ y;
+    }
+    out
+}
+
+/// Encrypts `plaintext` under AES-CBC, padding it to a block boundary with PKCS#7. `iv` must be
+/// exactly [`IV_LEN`] bytes; see its docs on IV reuse.
+pub fn cbc_encrypt(key: &[u8], iv: &[u8; IV_LEN], plaintext: &[u8]) -> Result<Vec<u8>, InvalidLength> {
+    let aes_key = AesKey::new(key)?;
+
+    let pad_len = BLOCK_SIZE - (plaintext.len() % BLOCK_SIZE);
+    let mut padded = Vec::with_capacity(plaintext.len() + pad_len);
+    padded.extend_from_slice(plaintext);
+    padded.resize(padded.len() + pad_len, pad_len as u8);
+
+    let mut out = Vec::with_capacity(padded.len());
+    let mut prev = *iv;
+    for block in padded.chunks_exact(BLOCK_SIZE) {
+        // `chunks_exact(BLOCK_SIZE)` guarantees each `block` is exactly `BLOCK_SIZE` bytes, so this
+        // conversion never actually fails.
+        let block: &[u8; BLOCK_SIZE] = block.try_into().map_err(|_| InvalidLength)?;
+        let encrypted = aes_key.encrypt_block(&xor_block(block, &prev));
+        out.extend_from_slice(&encrypted);
+        prev = encrypted;
+    }
+    Ok(out)
+}
+
+/// Error returned when an AES-CBC ciphertext can't be decrypted and unpadded: its length isn't a
+/// positive multiple of [`BLOCK_SIZE`], its trailing PKCS#7 padding is malformed, or the key is
+/// not 16 or 32 bytes. This is a single opaque variant covering all three cases, so callers can't
+/// distinguish why decryption failed.
+#[derive(Debug)]
+pub struct UnpadError;
+
+/// Decrypts an AES-CBC ciphertext produced by [`cbc_encrypt`] (or an equivalent PKCS#7-padded
+/// CBC encryption) under the same `key` and `iv`.
+pub fn cbc_decrypt(key: &[u8], iv: &[u8; IV_LEN], ciphertext: &[u8]) -> Result<Vec<u8>, UnpadError> {
+    if ciphertext.is_empty() || ciphertext.len() % BLOCK_SIZE != 0 {
+        return Err(UnpadError);
+    }
+    let aes_key = AesDecryptKey::new(key).map_err(|_| UnpadError)?;
+
+    let mut out = Vec::with_capacity(ciphertext.len());
+    let mut prev = *iv;
+    for block in ciphertext.chunks_exact(BLOCK_SIZE) {
+        // `chunks_exact(BLOCK_SIZE)` guarantees each `block` is exactly `BLOCK_SIZE` bytes, so this
+        // conversion never actually fails.
+        let block: &[u8; BLOCK_SIZE] = block.try_into().map_err(|_| UnpadError)?;
+        out.extend_from_slice(&xor_block(&aes_key.decrypt_block(block), &prev));
+        prev = *block;
+    }
+
+    strip_pkcs7_padding(&mut out)?;
+    Ok(out)
+}
+
+/// Strips and validates PKCS#7 padding in place. Rejects a padding byte of zero, one exceeding
+/// [`BLOCK_SIZE`], or one whose value doesn't match every byte it claims to cover.
+fn strip_pkcs7_padding(buf: &mut Vec<u8>) -> Result<(), UnpadError> {
+    let pad_len = *buf.last().ok_or(UnpadError)? as usize;
+    if pad_len == 0 || pad_len > BLOCK_SIZE || pad_len > buf.len() {
+        return Err(UnpadError);
+    }
+    let pad_start = buf.len() - pad_len;
+    let padding = buf.get(pad_start..).ok_or(UnpadError)?;
+    if padding.iter().any(|&b| b as usize != pad_len) {
+        return Err(UnpadError);
+    }
+    buf.truncate(pad_start);
+    Ok(())
+}
+
+/// Error returned when an encrypt-then-MAC message fails to authenticate or unpad. A single
+/// opaque variant covers both the MAC mismatch and the padding failure, so neither a caller nor a
+/// timing side channel can distinguish "bad tag" from "bad padding" -- the classic padding-oracle
+/// vector for CBC-then-MAC constructions.
+#[derive(Debug)]
+pub struct VerifyError;
+
+/// Length in bytes of the HMAC-SHA-256 tag appended by [`encrypt_then_mac`].
+const TAG_LEN: usize = 32;
+
+/// Encrypts `plaintext` under AES-CBC with PKCS#7 padding using `cipher_key`, then authenticates
+/// `iv || ciphertext` with `HMAC-SHA-256(mac_key, ...)` and appends the tag. `iv` must be exactly
+/// [`IV_LEN`] bytes; see its docs on IV reuse.
+///
+/// Wire format: `iv || ciphertext || tag`.
+pub fn encrypt_then_mac(
+    cipher_key: &[u8],
+    mac_key: &[u8],
+    iv: &[u8; IV_LEN],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, InvalidLength> {
+    let ciphertext = cbc_encrypt(cipher_key, iv, plaintext)?;
+
+    let mut mac_input = Vec::with_capacity(IV_LEN + ciphertext.len());
+    mac_input.extend_from_slice(iv);
+    mac_input.extend_from_slice(&ciphertext);
+    let mut mac = HmacSha256::new_from_slice(mac_key).map_err(|_| InvalidLength)?;
+    mac.update(&mac_input);
+    let tag = mac.finalize();
+
+    let mut out = Vec::with_capacity(mac_input.len() + tag.len());
+    out.extend_from_slice(&mac_input);
+    out.extend_from_slice(&tag);
+    Ok(out)
+}
+
+/// Verifies and decrypts a message produced by [`encrypt_then_mac`] under the same `cipher_key`
+/// and `mac_key`. The HMAC tag is checked in constant time before any padding is inspected; see
+/// [`VerifyError`].
+pub fn decrypt_and_verify(
+    cipher_key: &[u8],
+    mac_key: &[u8],
+    message: &[u8],
+) -> Result<Vec<u8>, VerifyError> {
+    if message.len() < IV_LEN + TAG_LEN {
+        return Err(VerifyError);
+    }
+    let (header, tag) = message.split_at(message.len() - TAG_LEN);
+    let (iv, ciphertext) = header.split_at(IV_LEN);
+
+    let mut mac = HmacSha256::new_from_slice(mac_key).map_err(|_| VerifyError)?;
+    mac.update(header);
+    mac.verify_slice(tag).map_err(|_| VerifyError)?;
+
+    let iv: &[u8; IV_LEN] = iv.try_into().map_err(|_| VerifyError)?;
+    cbc_decrypt(cipher_key, iv, ciphertext).map_err(|_| VerifyError)
+}
+
+/// Derives the 5-byte QUIC header-protection mask for AES (RFC 9001 section 5.4.3): the first 5
+/// bytes of a single ECB block encryption of `sample` under `hp_key`. The caller XORs `mask[0]`'s
+/// low bits into the first header byte and `mask[1..5]` into the packet number bytes.
+///
+/// Returns [`InvalidLength`] if `sample` is not exactly [`BLOCK_SIZE`] bytes, rather than indexing
+/// past the end of a shorter input.
+pub fn header_protection_mask(hp_key: &[u8], sample: &[u8]) -> Result<[u8; 5], InvalidLength> {
+    let sample: &[u8; BLOCK_SIZE] = sample.try_into().map_err(|_| InvalidLength)?;
+    let block = AesKey::new(hp_key)?.encrypt_block(sample);
+
+    let mut mask = [0u8; 5];
+    let (first_five, _) = block.split_at(5);
+    mask.copy_from_slice(first_five);
+    Ok(mask)
+}
+
+/// Derives the 5-byte QUIC header-protection mask for ChaCha20 (RFC 9001 section 5.4.4). The
+/// first 4 bytes of `sample` form the little-endian block counter, and the remaining 12 form the
+/// nonce; the mask is the ChaCha20 keystream applied to five zero bytes.
+///
+/// Returns [`InvalidLength`] if `sample` is not exactly [`BLOCK_SIZE`] bytes.
+pub fn chacha20_header_protection_mask(
+    hp_key: &[u8; 32],
+    sample: &[u8],
+) -> Result<[u8; 5], InvalidLength> {
+    let sample: &[u8; BLOCK_SIZE] = sample.try_into().map_err(|_| InvalidLength)?;
+    let (counter_bytes, nonce_bytes) = sample.split_at(4);
+    let counter = u32::from_le_bytes(counter_bytes.try_into().map_err(|_| InvalidLength)?);
+    let nonce: [u8; 12] = nonce_bytes.try_into().map_err(|_| InvalidLength)?;
+
+    let zeros = [0u8; 5];
+    let mut mask = [0u8; 5];
+    // Safety:
+    // - `mask` and `zeros` are both 5 bytes, matching `in_len`.
+    // - `hp_key` and `nonce` are fixed-size arrays matching CRYPTO_chacha_20's key/nonce lengths.
+    unsafe {
+        bssl_sys::CRYPTO_chacha_20(
+            mask.as_mut_ptr(),
+            zeros.as_ptr(),
+            zeros.len(),
+            hp_key.as_ptr(),
+            nonce.as_ptr(),
+            counter,
+        )
+    };
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_protection_mask_rejects_short_sample() {
+        let hp_key = [0u8; 16];
+        assert!(header_protection_mask(&hp_key, &[0u8; BLOCK_SIZE - 1]).is_err());
+    }
+
+    #[test]
+    fn header_protection_mask_is_deterministic() {
+        let hp_key = [0x42u8; 16];
+        let sample = [0x24u8; BLOCK_SIZE];
+        let mask1 = header_protection_mask(&hp_key, &sample).expect("valid sample length");
+        let mask2 = header_protection_mask(&hp_key, &sample).expect("valid sample length");
+        assert_eq!(mask1, mask2);
+    }
+
+    #[test]
+    fn header_protection_mask_differs_per_key() {
+        let sample = [0x24u8; BLOCK_SIZE];
+        let mask1 =
+            header_protection_mask(&[0x01u8; 16], &sample).expect("valid sample length");
+        let mask2 =
+            header_protection_mask(&[0x02u8; 16], &sample).expect("valid sample length");
+        assert_ne!(mask1, mask2);
+    }
+
+    #[test]
+    fn chacha20_header_protection_mask_rejects_short_sample() {
+        let hp_key = [0u8; 32];
+        assert!(chacha20_header_protection_mask(&hp_key, &[0u8; BLOCK_SIZE - 1]).is_err());
+    }
+
+    #[test]
+    fn chacha20_header_protection_mask_is_deterministic() {
+        let hp_key = [0x11u8; 32];
+        let sample = [0x22u8; BLOCK_SIZE];
+        let mask1 = chacha20_header_protection_mask(&hp_key, &sample).expect("valid sample");
+        let mask2 = chacha20_header_protection_mask(&hp_key, &sample).expect("valid sample");
+        assert_eq!(mask1, mask2);
+    }
+
+    #[test]
+    fn cbc_encrypt_then_decrypt_round_trips() {
+        let key = [0x2bu8; 16];
+        let iv = [0x00u8; IV_LEN];
+        let plaintext = b"this message is not block aligned";
+        let ciphertext = cbc_encrypt(&key, &iv, plaintext).expect("valid key length");
+        assert_eq!(ciphertext.len() % BLOCK_SIZE, 0);
+        let decrypted = cbc_decrypt(&key, &iv, &ciphertext).expect("valid ciphertext");
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn cbc_encrypt_then_decrypt_round_trips_on_block_boundary() {
+        let key = [0x2bu8; 32];
+        let iv = [0x11u8; IV_LEN];
+        let plaintext = [0x42u8; BLOCK_SIZE * 2];
+        let ciphertext = cbc_encrypt(&key, &iv, &plaintext).expect("valid key length");
+        // A full extra padding block is appended even when the input is already block-aligned.
+        assert_eq!(ciphertext.len(), plaintext.len() + BLOCK_SIZE);
+        let decrypted = cbc_decrypt(&key, &iv, &ciphertext).expect("valid ciphertext");
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn cbc_decrypt_rejects_wrong_length_ciphertext() {
+        let key = [0x2bu8; 16];
+        let iv = [0x00u8; IV_LEN];
+        assert!(cbc_decrypt(&key, &iv, &[0u8; BLOCK_SIZE - 1]).is_err());
+    }
+
+    #[test]
+    fn cbc_decrypt_rejects_tampered_padding() {
+        let key = [0x2bu8; 16];
+        let iv = [0x00u8; IV_LEN];
+        let mut ciphertext = cbc_encrypt(&key, &iv, b"hello").expect("valid key length");
+        let last = ciphertext.len() - 1;
+        ciphertext[last] ^= 0xff;
+        assert!(cbc_decrypt(&key, &iv, &ciphertext).is_err());
+    }
+
+    #[test]
+    fn encrypt_then_mac_round_trips() {
+        let cipher_key = [0x01u8; 16];
+        let mac_key = [0x02u8; 32];
+        let iv = [0x03u8; IV_LEN];
+        let plaintext = b"encrypt then mac";
+
+        let message =
+            encrypt_then_mac(&cipher_key, &mac_key, &iv, plaintext).expect("valid key lengths");
+        let decrypted =
+            decrypt_and_verify(&cipher_key, &mac_key, &message).expect("authentic message");
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn decrypt_and_verify_rejects_tampered_tag() {
+        let cipher_key = [0x01u8; 16];
+        let mac_key = [0x02u8; 32];
+        let iv = [0x03u8; IV_LEN];
+        let mut message = encrypt_then_mac(&cipher_key, &mac_key, &iv, b"hello world")
+            .expect("valid key lengths");
+        let last = message.len() - 1;
+        message[last] ^= 0xff;
+        assert!(decrypt_and_verify(&cipher_key, &mac_key, &message).is_err());
+    }
+
+    #[test]
+    fn decrypt_and_verify_rejects_tampered_ciphertext() {
+        let cipher_key = [0x01u8; 16];
+        let mac_key = [0x02u8; 32];
+        let iv = [0x03u8; IV_LEN];
+        let mut message = encrypt_then_mac(&cipher_key, &mac_key, &iv, b"hello world")
+            .expect("valid key lengths");
+        message[IV_LEN] ^= 0xff;
+        assert!(decrypt_and_verify(&cipher_key, &mac_key, &message).is_err());
+    }
+
+    #[test]
+    fn decrypt_and_verify_rejects_short_message() {
+        let cipher_key = [0x01u8; 16];
+        let mac_key = [0x02u8; 32];
+        assert!(decrypt_and_verify(&cipher_key, &mac_key, &[0u8; IV_LEN]).is_err());
+    }
+}
@@ -15,10 +15,18 @@
 
 use foreign_types::{ForeignTypeRef, Opaque};
 
+/// openssl sha1 digest algorithm
+#[derive(Clone)]
+pub struct Sha1 {}
+
 /// openssl sha256 digest algorithm
 #[derive(Clone)]
 pub struct Sha256 {}
 
+/// openssl sha384 digest algorithm
+#[derive(Clone)]
+pub struct Sha384 {}
+
 /// openssl sha512 digest algorithm
 #[derive(Clone)]
 pub struct Sha512 {}
@@ -26,7 +34,7 @@ pub struct Sha512 {}
 /// A reference to an [`Md`], which abstracts the details of a specific hash function allowing code
 /// to deal with the concept of a "hash function" without needing to know exactly which hash function
 /// it is
-pub(crate) struct MdRef(Opaque);
+pub struct MdRef(Opaque);
 
 unsafe impl ForeignTypeRef for MdRef {
     type CType = bssl_sys::EVP_MD;
@@ -38,6 +46,14 @@ pub(crate) trait Md {
     fn get_md() -> &'static MdRef;
 }
 
+impl Md for Sha1 {
+    fn get_md() -> &'static MdRef {
+        // Safety:
+        // - this always returns a valid pointer to an EVP_MD
+        unsafe { MdRef::from_ptr(bssl_sys::EVP_sha1() as *mut _) }
+    }
+}
+
 impl Md for Sha256 {
     fn get_md() -> &'static MdRef {
         // Safety:
@@ -46,6 +62,14 @@ impl Md for Sha256 {
     }
 }
 
+impl Md for Sha384 {
+    fn get_md() -> &'static MdRef {
+        // Safety:
+        // - this always returns a valid pointer to an EVP_MD
+        unsafe { MdRef::from_ptr(bssl_sys::EVP_sha384() as *mut _) }
+    }
+}
+
 impl Md for Sha512 {
     fn get_md() -> &'static MdRef {
         // Safety:
@@ -53,3 +77,27 @@ impl Md for Sha512 {
         unsafe { MdRef::from_ptr(bssl_sys::EVP_sha512() as *mut _) }
     }
 }
+
+/// Returns the SHA-1 message digest, for callers that pick a hash function at runtime (e.g.
+/// [`crate::hmac::HmacCtx`]) rather than through the typed [`Md`] generics.
+pub fn sha1_md() -> &'static MdRef {
+    Sha1::get_md()
+}
+
+/// Returns the SHA-256 message digest, for callers that pick a hash function at runtime (e.g.
+/// [`crate::hmac::HmacCtx`]) rather than through the typed [`Md`] generics.
+pub fn sha256_md() -> &'static MdRef {
+    Sha256::get_md()
+}
+
+/// Returns the SHA-384 message digest, for callers that pick a hash function at runtime (e.g.
+/// [`crate::hmac::HmacCtx`]) rather than through the typed [`Md`] generics.
+pub fn sha384_md() -> &'static MdRef {
+    Sha384::get_md()
+}
+
+/// Returns the SHA-512 message digest, for callers that pick a hash function at runtime (e.g.
+/// [`crate::hmac::HmacCtx`]) rather than through the typed [`Md`] generics.
+pub fn sha512_md() -> &'static MdRef {
+    Sha512::get_md()
+}
@@ -0,0 +1,149 @@
+/* Copyright (c) 2024, Google Inc.
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+ * SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+ * OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+ * CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ */
+use crate::{
+    digest::{Md, Sha256, Sha512},
+    PanicResultHandler,
+};
+use foreign_types::ForeignTypeRef;
+
+/// Error output when the requested output length is not a valid HKDF-Expand length, i.e. it
+/// exceeds `255 * HashLen`.
+#[derive(Debug)]
+pub struct InvalidLength;
+
+/// A pseudorandom key (PRK) produced by [`extract`], sized to the output of the hash function
+/// used to produce it. This is kept as its own type, rather than a plain `Vec<u8>`, so that
+/// callers can't accidentally pass raw input keying material to [`expand`].
+pub struct Prk(Vec<u8>);
+
+impl Prk {
+    /// The raw bytes of the pseudorandom key.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// One shot HKDF-SHA-256 operation: [`extract`] followed by [`expand`].
+pub fn hkdf_sha_256(salt: &[u8], ikm: &[u8], info: &[u8], out: &mut [u8]) -> Result<(), InvalidLength> {
+    expand::<Sha256>(&extract::<Sha256>(salt, ikm), info, out)
+}
+
+/// One shot HKDF-SHA-512 operation: [`extract`] followed by [`expand`].
+pub fn hkdf_sha_512(salt: &[u8], ikm: &[u8], info: &[u8], out: &mut [u8]) -> Result<(), InvalidLength> {
+    expand::<Sha512>(&extract::<Sha512>(salt, ikm), info, out)
+}
+
+/// RFC 5869 HKDF-Extract: derives a pseudorandom key from `ikm` (input keying material) using an
+/// optional non-secret `salt`. As in the RFC, an empty `salt` is treated as a `HashLen`-byte
+/// string of zeros.
+pub fn extract<M: Md>(salt: &[u8], ikm: &[u8]) -> Prk {
+    let mut buf = vec![0_u8; bssl_sys::EVP_MAX_MD_SIZE as usize];
+    let mut out_len: usize = 0;
+    // Safety:
+    // - `buf` is sized to the largest digest BoringSSL supports, which bounds any `M::get_md()`.
+    // - `HKDF_extract` returns 0 only on allocation failure, which we treat as fatal like the
+    //   rest of this crate's C API wrappers.
+    unsafe {
+        bssl_sys::HKDF_extract(
+            buf.as_mut_ptr(),
+            &mut out_len,
+            M::get_md().as_ptr(),
+            ikm.as_ptr(),
+            ikm.len(),
+            salt.as_ptr(),
+            salt.len(),
+        )
+    }
+    .panic_if_error();
+    buf.truncate(out_len);
+    Prk(buf)
+}
+
+/// RFC 5869 HKDF-Expand: stretches `prk` into `out.len()` bytes of output keying material, bound
+/// to the context-specific `info`. Returns [`InvalidLength`] if `out.len() > 255 * HashLen`.
+pub fn expand<M: Md>(prk: &Prk, info: &[u8], out: &mut [u8]) -> Result<(), InvalidLength> {
+    // Safety: `M::get_md()` always returns a valid pointer to a statically allocated EVP_MD.
+    let hash_len = unsafe { bssl_sys::EVP_MD_size(M::get_md().as_ptr()) } as usize;
+    if out.len() > 255 * hash_len {
+        return Err(InvalidLength);
+    }
+
+    // Safety:
+    // - `out` is the caller-provided output buffer, sized to `out.len()`.
+    // - `HKDF_expand` returns 0 if `out.len()` exceeds `255 * HashLen`, which is already checked
+    //   above, or on allocation failure; both are treated as fatal here.
+    unsafe {
+        bssl_sys::HKDF_expand(
+            out.as_mut_ptr(),
+            out.len(),
+            M::get_md().as_ptr(),
+            prk.as_bytes().as_ptr(),
+            prk.as_bytes().len(),
+            info.as_ptr(),
+            info.len(),
+        )
+    }
+    .panic_if_error();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digest::Sha256;
+
+    // RFC 5869 Appendix A.1 test case 1 (Basic test case with SHA-256)
+    #[test]
+    fn hkdf_sha256_rfc5869_test_case_1() {
+        let ikm = [0x0b_u8; 22];
+        let salt = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c,
+        ];
+        let info = [
+            0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9,
+        ];
+        let expected_okm = [
+            0x3c, 0xb2, 0x5f, 0x25, 0xfa, 0xac, 0xd5, 0x7a, 0x90, 0x43, 0x4f, 0x64, 0xd0, 0x36,
+            0x2f, 0x2a, 0x2d, 0x2d, 0x0a, 0x90, 0xcf, 0x1a, 0x5a, 0x4c, 0x5d, 0xb0, 0x2d, 0x56,
+            0xec, 0xc4, 0xc5, 0xbf, 0x34, 0x00, 0x72, 0x08, 0xd5, 0xb8, 0x87, 0x18, 0x58, 0x65,
+        ];
+
+        let prk = extract::<Sha256>(&salt, &ikm);
+        let mut okm = [0_u8; 42];
+        expand::<Sha256>(&prk, &info, &mut okm).expect("valid length");
+        assert_eq!(okm, expected_okm);
+    }
+
+    #[test]
+    fn expand_rejects_too_long_output() {
+        let prk = extract::<Sha256>(b"salt", b"ikm");
+        let mut out = vec![0_u8; 255 * 32 + 1];
+        assert!(expand::<Sha256>(&prk, b"info", &mut out).is_err());
+    }
+
+    #[test]
+    fn one_shot_matches_extract_then_expand() {
+        let salt = b"salt";
+        let ikm = b"input keying material";
+        let info = b"context info";
+
+        let mut expected = [0_u8; 32];
+        expand::<Sha256>(&extract::<Sha256>(salt, ikm), info, &mut expected).expect("valid length");
+
+        let mut actual = [0_u8; 32];
+        hkdf_sha_256(salt, ikm, info, &mut actual).expect("valid length");
+        assert_eq!(actual, expected);
+    }
+}
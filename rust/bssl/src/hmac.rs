@@ -13,7 +13,7 @@
  * CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
  */
 use crate::{
-    digest::{Md, Sha256, Sha512},
+    digest::{Md, MdRef, Sha1, Sha256, Sha384, Sha512},
     PanicResultHandler,
 };
 use bssl_sys::HMAC_CTX;
@@ -21,19 +21,35 @@ use core::{marker::PhantomData, ptr};
 use foreign_types::ForeignTypeRef;
 use libc::{c_uint, c_void, size_t};
 
+/// One shot Hmac SHA-1 operation
+pub fn hmac_sha_1(key: &[u8], data: &[u8]) -> Result<[u8; 20], InvalidLength> {
+    hmac::<20, Sha1>(key, data)
+}
+
 /// One shot Hmac SHA-256 operation
 pub fn hmac_sha_256(key: &[u8], data: &[u8]) -> Result<[u8; 32], InvalidLength> {
     hmac::<32, Sha256>(key, data)
 }
 
+/// One shot Hmac SHA-384 operation
+pub fn hmac_sha_384(key: &[u8], data: &[u8]) -> Result<[u8; 48], InvalidLength> {
+    hmac::<48, Sha384>(key, data)
+}
+
 /// One shot Hmac SHA-512 operation
 pub fn hmac_sha_512(key: &[u8], data: &[u8]) -> Result<[u8; 64], InvalidLength> {
     hmac::<64, Sha512>(key, data)
 }
 
+/// Hmac SHA-1 impl
+pub struct HmacSha1(HmacImpl<20, Sha1>);
+
 /// Hmac SHA-256 impl
 pub struct HmacSha256(HmacImpl<32, Sha256>);
 
+/// Hmac SHA-384 impl
+pub struct HmacSha384(HmacImpl<48, Sha384>);
+
 /// Hmac SHA-512 impl
 pub struct HmacSha512(HmacImpl<64, Sha512>);
 
@@ -83,6 +99,15 @@ pub trait Hmac<const N: usize>: Sized {
     /// Obtain the hmac computation consuming the hmac instance
     fn finalize(self) -> [u8; N];
 
+    /// Re-prime the context for a new message, keeping the existing key schedule. Avoids
+    /// allocating a fresh context for every message when computing many MACs with the same key.
+    fn reset(&mut self);
+
+    /// Obtain the hmac computation and reset the context for a new message with the same key,
+    /// equivalent to calling [`Hmac::finalize`]'s hashing followed by [`Hmac::reset`] without
+    /// consuming `self`.
+    fn finalize_reset(&mut self) -> [u8; N];
+
     /// Check that the tag value is correct for the processed input
     fn verify_slice(self, tag: &[u8]) -> Result<(), MacError>;
 
@@ -93,6 +118,44 @@ pub trait Hmac<const N: usize>: Sized {
     fn verify_truncated_left(self, tag: &[u8]) -> Result<(), MacError>;
 }
 
+impl Hmac<20> for HmacSha1 {
+    fn new(key: [u8; 20]) -> Self {
+        Self(HmacImpl::new(key))
+    }
+
+    fn new_from_slice(key: &[u8]) -> Result<Self, InvalidLength> {
+        HmacImpl::new_from_slice(key).map(Self)
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data)
+    }
+
+    fn finalize(self) -> [u8; 20] {
+        self.0.finalize()
+    }
+
+    fn reset(&mut self) {
+        self.0.reset()
+    }
+
+    fn finalize_reset(&mut self) -> [u8; 20] {
+        self.0.finalize_reset()
+    }
+
+    fn verify_slice(self, tag: &[u8]) -> Result<(), MacError> {
+        self.0.verify_slice(tag)
+    }
+
+    fn verify(self, tag: [u8; 20]) -> Result<(), MacError> {
+        self.0.verify(tag)
+    }
+
+    fn verify_truncated_left(self, tag: &[u8]) -> Result<(), MacError> {
+        self.0.verify_truncated_left(tag)
+    }
+}
+
 impl Hmac<32> for HmacSha256 {
     fn new(key: [u8; 32]) -> Self {
         Self(HmacImpl::new(key))
@@ -110,6 +173,14 @@ impl Hmac<32> for HmacSha256 {
         self.0.finalize()
     }
 
+    fn reset(&mut self) {
+        self.0.reset()
+    }
+
+    fn finalize_reset(&mut self) -> [u8; 32] {
+        self.0.finalize_reset()
+    }
+
     fn verify_slice(self, tag: &[u8]) -> Result<(), MacError> {
         self.0.verify_slice(tag)
     }
@@ -123,6 +194,44 @@ impl Hmac<32> for HmacSha256 {
     }
 }
 
+impl Hmac<48> for HmacSha384 {
+    fn new(key: [u8; 48]) -> Self {
+        Self(HmacImpl::new(key))
+    }
+
+    fn new_from_slice(key: &[u8]) -> Result<Self, InvalidLength> {
+        HmacImpl::new_from_slice(key).map(Self)
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data)
+    }
+
+    fn finalize(self) -> [u8; 48] {
+        self.0.finalize()
+    }
+
+    fn reset(&mut self) {
+        self.0.reset()
+    }
+
+    fn finalize_reset(&mut self) -> [u8; 48] {
+        self.0.finalize_reset()
+    }
+
+    fn verify_slice(self, tag: &[u8]) -> Result<(), MacError> {
+        self.0.verify_slice(tag)
+    }
+
+    fn verify(self, tag: [u8; 48]) -> Result<(), MacError> {
+        self.0.verify(tag)
+    }
+
+    fn verify_truncated_left(self, tag: &[u8]) -> Result<(), MacError> {
+        self.0.verify_truncated_left(tag)
+    }
+}
+
 impl Hmac<64> for HmacSha512 {
     fn new(key: [u8; 64]) -> Self {
         Self(HmacImpl::new(key))
@@ -140,6 +249,14 @@ impl Hmac<64> for HmacSha512 {
         self.0.finalize()
     }
 
+    fn reset(&mut self) {
+        self.0.reset()
+    }
+
+    fn finalize_reset(&mut self) -> [u8; 64] {
+        self.0.finalize_reset()
+    }
+
     fn verify_slice(self, tag: &[u8]) -> Result<(), MacError> {
         self.0.verify_slice(tag)
     }
@@ -226,6 +343,27 @@ impl<const N: usize, M: Md> HmacImpl<N, M> {
         buf
     }
 
+    /// Re-prime the context for a new message, keeping the existing key schedule, by passing a
+    /// null key and null md to `HMAC_Init_ex`.
+    fn reset(&mut self) {
+        // Safety:
+        // - `self.ctx` was already initialized by `new_from_slice` with a key and md, so passing
+        //   null here re-primes it without the "key is null but md changed" error case.
+        unsafe { bssl_sys::HMAC_Init_ex(self.ctx, ptr::null(), 0, ptr::null(), ptr::null_mut()) }
+            .panic_if_error()
+    }
+
+    /// Obtain the hmac computation and reset the context for a new message with the same key.
+    fn finalize_reset(&mut self) -> [u8; N] {
+        let mut buf = [0_u8; N];
+        let mut size: c_uint = 0;
+        // Safety: same as `finalize`, above.
+        unsafe { bssl_sys::HMAC_Final(self.ctx, buf.as_mut_ptr(), &mut size as *mut c_uint) }
+            .panic_if_error();
+        self.reset();
+        buf
+    }
+
     /// Check that the tag value is correct for the processed input
     fn verify(self, tag: [u8; N]) -> Result<(), MacError> {
         self.verify_slice(&tag)
@@ -274,6 +412,71 @@ impl<const N: usize, M: Md> Drop for HmacImpl<N, M> {
     }
 }
 
+/// A runtime-selectable Hmac context, for callers that pick a digest from a value only known at
+/// runtime (e.g. a negotiated protocol parameter) instead of a compile-time [`Md`] generic.
+pub struct HmacCtx {
+    ctx: *mut HMAC_CTX,
+    output_len: usize,
+}
+
+impl HmacCtx {
+    /// Create a new hmac context from a runtime-selected digest and a variable size key.
+    pub fn new(md: &MdRef, key: &[u8]) -> Self {
+        // Safety: HMAC_CTX_new panics if allocation fails
+        let ctx = unsafe { bssl_sys::HMAC_CTX_new() };
+        ctx.panic_if_error();
+
+        // Safety:
+        // - `ctx` was just allocated above and is non-null.
+        // - key is guaranteed to be non-null, so HMAC_Init_ex cannot hit the "key is null but md
+        //   changed" error case.
+        unsafe {
+            bssl_sys::HMAC_Init_ex(
+                ctx,
+                key.as_ptr() as *const c_void,
+                key.len(),
+                md.as_ptr(),
+                ptr::null_mut(),
+            )
+        }
+        .panic_if_error();
+
+        // Safety: `md.as_ptr()` is a valid, non-null EVP_MD.
+        let output_len = unsafe { bssl_sys::EVP_MD_size(md.as_ptr()) } as usize;
+
+        Self { ctx, output_len }
+    }
+
+    /// Update state using the provided data, can be called repeatedly
+    pub fn update(&mut self, data: &[u8]) {
+        unsafe {
+            // Safety: HMAC_Update will always return 1, in case it doesnt we panic
+            bssl_sys::HMAC_Update(self.ctx, data.as_ptr(), data.len())
+        }
+        .panic_if_error()
+    }
+
+    /// Obtain the hmac computation consuming the hmac instance, sized to the digest's output
+    /// length.
+    pub fn finalize(self) -> Vec<u8> {
+        let mut buf = vec![0_u8; self.output_len];
+        let mut size: c_uint = 0;
+        // Safety:
+        // - `buf` is sized to the output length queried from the EVP_MD used to init this context
+        // - on allocation failure we panic
+        unsafe { bssl_sys::HMAC_Final(self.ctx, buf.as_mut_ptr(), &mut size as *mut c_uint) }
+            .panic_if_error();
+        buf.truncate(size as usize);
+        buf
+    }
+}
+
+impl Drop for HmacCtx {
+    fn drop(&mut self) {
+        unsafe { bssl_sys::HMAC_CTX_free(self.ctx) }
+    }
+}
+
 // make sure key len is within a valid range
 fn validate_key_len(len: usize) -> bool {
     if len > bssl_sys::EVP_MAX_MD_BLOCK_SIZE as usize {
@@ -384,18 +587,85 @@ mod tests {
         assert!(hmac.verify(expected_hmac).is_ok())
     }
 
+    #[test]
+    fn finalize_reset_matches_separate_finalizes_with_same_key() {
+        let key: [u8; 20] = [0x0b; 20];
+        let data = b"Hi There";
+
+        let mut hmac: HmacSha256 = Hmac::new_from_slice(&key).expect("length is valid");
+        hmac.update(data);
+        let first = hmac.finalize_reset();
+
+        hmac.update(data);
+        let second = hmac.finalize_reset();
+
+        assert_eq!(first, second);
+
+        let mut fresh: HmacSha256 = Hmac::new_from_slice(&key).expect("length is valid");
+        fresh.update(data);
+        assert_eq!(fresh.finalize(), first);
+    }
+
+    #[test]
+    fn hmac_ctx_matches_typed_hmac_sha256() {
+        let key: [u8; 20] = [0x0b; 20];
+        let data = b"Hi There";
+
+        let mut typed = HmacSha256::new_from_slice(&key).expect("length is valid");
+        typed.update(data);
+        let typed_result = typed.finalize();
+
+        let mut ctx = HmacCtx::new(crate::digest::sha256_md(), &key);
+        ctx.update(data);
+        let ctx_result = ctx.finalize();
+
+        assert_eq!(&ctx_result, &typed_result);
+    }
+
+    #[test]
+    fn hmac_sha384_test() {
+        // RFC 4231 test case 1
+        let expected_hmac = [
+            0xaf, 0xd0, 0x39, 0x44, 0xd8, 0x48, 0x95, 0x62, 0x6b, 0x08, 0x25, 0xf4, 0xab, 0x46,
+            0x90, 0x7f, 0x15, 0xf9, 0xda, 0xdb, 0xe4, 0x10, 0x1e, 0xc6, 0x82, 0xaa, 0x03, 0x4c,
+            0x7c, 0xeb, 0xc5, 0x9c, 0xfa, 0xea, 0x9e, 0xa9, 0x07, 0x6e, 0xde, 0x7f, 0x4a, 0xf1,
+            0x52, 0xe8, 0xb2, 0xfa, 0x9c, 0xb6,
+        ];
+
+        let key: [u8; 20] = [0x0b; 20];
+        let data = b"Hi There";
+
+        let mut hmac = HmacSha384::new_from_slice(&key).expect("length is valid");
+        hmac.update(data);
+        let hmac_result: [u8; 48] = hmac.finalize();
+
+        assert_eq!(&hmac_result, &expected_hmac);
+    }
+
+    #[test]
+    fn hmac_sha_1_wycheproof_test_vectors() {
+        run_hmac_test_vectors::<20, Sha1>(HashAlg::Sha1);
+    }
+
     #[test]
     fn hmac_sha_256_wycheproof_test_vectors() {
         run_hmac_test_vectors::<32, Sha256>(HashAlg::Sha256);
     }
 
+    #[test]
+    fn hmac_sha_384_wycheproof_test_vectors() {
+        run_hmac_test_vectors::<48, Sha384>(HashAlg::Sha384);
+    }
+
     #[test]
     fn hmac_sha_512_wycheproof_test_vectors() {
         run_hmac_test_vectors::<64, Sha512>(HashAlg::Sha512);
     }
 
     enum HashAlg {
+        Sha1,
         Sha256,
+        Sha384,
         Sha512,
     }
 
@@ -403,7 +673,9 @@ mod tests {
     // https://github.com/google/wycheproof
     fn run_hmac_test_vectors<const N: usize, M: Md>(hash: HashAlg) {
         let test_name = match hash {
+            HashAlg::Sha1 => wycheproof::mac::TestName::HmacSha1,
             HashAlg::Sha256 => wycheproof::mac::TestName::HmacSha256,
+            HashAlg::Sha384 => wycheproof::mac::TestName::HmacSha384,
             HashAlg::Sha512 => wycheproof::mac::TestName::HmacSha512,
         };
 
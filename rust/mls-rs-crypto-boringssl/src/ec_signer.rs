@@ -0,0 +1,230 @@
+/* Copyright (c) 2024, Google Inc.
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+ * SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+ * OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+ * CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ */
+
+//! Signature key handling for [`crate::BoringsslCipherSuite`], backed by [`bssl_crypto::ec_signer`].
+//!
+//! Only the P-256 MLS cipher suite is supported today, since P-256 is the only curve
+//! `bssl_crypto::ec_signer` implements that an MLS cipher suite uses. By default, [`EcSigner`] treats `SignatureSecretKey` as
+//! the raw in-process key bytes. Callers that keep signing keys outside the process (macOS
+//! Keychain, Windows CNG, a PKCS#11/HSM token) can instead register a [`SigningBackend`] via
+//! [`EcSigner::with_backend`], in which case the `SignatureSecretKey` passed to `sign`/
+//! `signature_key_derive_public` is never read; the backend is used instead.
+
+use bssl_crypto::ec_signer::{EcPrivateKey, EcPublicKey, EcSignerError as RawEcSignerError};
+use mls_rs_core::crypto::{CipherSuite, SignaturePublicKey, SignatureSecretKey};
+use thiserror::Error;
+
+/// Error returned from unsuccessful EC signing operations.
+#[derive(Debug, Error)]
+pub enum EcSignerError {
+    /// The cipher suite is not a signature algorithm this module implements.
+    #[error("unsupported cipher suite")]
+    UnsupportedCipherSuite,
+    /// A BoringSSL EC operation (key parsing, signing, or verification) failed.
+    #[error("EC signing operation failed")]
+    CryptoError,
+    /// [`EcSigner::signature_key_generate`] was called on an instance backed by a
+    /// [`SigningBackend`], which owns its key material externally and cannot mint a new
+    /// in-process `SignatureSecretKey` for it.
+    #[error("signature key generation is not supported for a backend-held key")]
+    BackendKeyGenerationUnsupported,
+}
+
+impl From<RawEcSignerError> for EcSignerError {
+    fn from(_: RawEcSignerError) -> Self {
+        EcSignerError::CryptoError
+    }
+}
+
+/// An external signer for a signature key that lives outside this process, such as in the macOS
+/// Keychain, Windows CNG, or a PKCS#11/HSM token. The raw private key bytes never need to pass
+/// through this crate; only signatures and the public key do.
+pub trait SigningBackend: Send + Sync {
+    /// Signs `data`, returning a DER-encoded ECDSA signature over its SHA-256 digest.
+    fn sign(&self, data: &[u8]) -> Result<Vec<u8>, EcSignerError>;
+
+    /// Returns the public key corresponding to the externally-held private key.
+    fn public_key(&self) -> Result<SignaturePublicKey, EcSignerError>;
+}
+
+/// Signs and verifies with the MLS signature scheme for a single cipher suite, either over raw
+/// in-process key bytes or through a registered [`SigningBackend`].
+pub struct EcSigner {
+    cipher_suite: CipherSuite,
+    backend: Option<Box<dyn SigningBackend>>,
+}
+
+fn supports(cipher_suite: CipherSuite) -> bool {
+    cipher_suite == CipherSuite::P256_AES128
+}
+
+impl EcSigner {
+    /// New EcSigner over the raw in-process key path, or `None` if `cipher_suite` isn't a
+    /// signature algorithm this module implements.
+    pub fn new(cipher_suite: CipherSuite) -> Option<Self> {
+        supports(cipher_suite).then_some(Self { cipher_suite, backend: None })
+    }
+
+    /// New EcSigner that routes `sign` and `signature_key_derive_public` through `backend`
+    /// instead of treating `SignatureSecretKey` as raw key bytes. Returns `None` if
+    /// `cipher_suite` isn't a signature algorithm this module implements.
+    pub fn with_backend(cipher_suite: CipherSuite, backend: Box<dyn SigningBackend>) -> Option<Self> {
+        supports(cipher_suite).then_some(Self { cipher_suite, backend: Some(backend) })
+    }
+
+    /// The cipher suite this signer was constructed for.
+    pub fn cipher_suite(&self) -> CipherSuite {
+        self.cipher_suite
+    }
+
+    /// Generates a new signature key pair. Not supported when a [`SigningBackend`] is
+    /// registered, since the backend owns its key material externally.
+    pub fn signature_key_generate(
+        &self,
+    ) -> Result<(SignatureSecretKey, SignaturePublicKey), EcSignerError> {
+        if self.backend.is_some() {
+            return Err(EcSignerError::BackendKeyGenerationUnsupported);
+        }
+        let private_key = EcPrivateKey::generate()?;
+        let public_key = private_key.public_key()?;
+        Ok((
+            SignatureSecretKey::from(private_key.to_bytes()),
+            SignaturePublicKey::from(public_key.to_bytes()),
+        ))
+    }
+
+    /// Derives the public key for `secret_key`. If a [`SigningBackend`] is registered,
+    /// `secret_key` is ignored and the backend's public key is returned instead.
+    pub fn signature_key_derive_public(
+        &self,
+        secret_key: &SignatureSecretKey,
+    ) -> Result<SignaturePublicKey, EcSignerError> {
+        if let Some(backend) = &self.backend {
+            return backend.public_key();
+        }
+        let private_key = EcPrivateKey::from_bytes(secret_key)?;
+        Ok(SignaturePublicKey::from(private_key.public_key()?.to_bytes()))
+    }
+
+    /// Signs `data`. If a [`SigningBackend`] is registered, `secret_key` is ignored and the
+    /// signature is produced by the backend instead, so the raw key bytes never enter this
+    /// process for that path.
+    pub fn sign(
+        &self,
+        secret_key: &SignatureSecretKey,
+        data: &[u8],
+    ) -> Result<Vec<u8>, EcSignerError> {
+        if let Some(backend) = &self.backend {
+            return backend.sign(data);
+        }
+        Ok(EcPrivateKey::from_bytes(secret_key)?.sign(data)?)
+    }
+
+    /// Verifies `signature` over `data`. Verification is always performed in-crate via
+    /// BoringSSL, regardless of whether signing goes through a [`SigningBackend`].
+    pub fn verify(
+        &self,
+        public_key: &SignaturePublicKey,
+        signature: &[u8],
+        data: &[u8],
+    ) -> Result<(), EcSignerError> {
+        Ok(EcPublicKey::from_bytes(public_key)?.verify(data, signature)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedBackend {
+        private_key: EcPrivateKey,
+    }
+
+    impl SigningBackend for FixedBackend {
+        fn sign(&self, data: &[u8]) -> Result<Vec<u8>, EcSignerError> {
+            Ok(self.private_key.sign(data)?)
+        }
+
+        fn public_key(&self) -> Result<SignaturePublicKey, EcSignerError> {
+            Ok(SignaturePublicKey::from(self.private_key.public_key()?.to_bytes()))
+        }
+    }
+
+    #[test]
+    fn raw_key_sign_then_verify_round_trips() {
+        let signer = EcSigner::new(CipherSuite::P256_AES128).expect("cipher suite is supported");
+        let (secret_key, public_key) = signer
+            .signature_key_generate()
+            .expect("key generation should succeed");
+        assert_eq!(
+            signer
+                .signature_key_derive_public(&secret_key)
+                .expect("public key derivation should succeed"),
+            public_key
+        );
+
+        let sig = signer.sign(&secret_key, b"hello world").expect("signing should succeed");
+        signer
+            .verify(&public_key, &sig, b"hello world")
+            .expect("verification should succeed");
+    }
+
+    #[test]
+    fn unsupported_cipher_suite_returns_none() {
+        assert!(EcSigner::new(CipherSuite::CURVE25519_AES128).is_none());
+    }
+
+    #[test]
+    fn backend_signs_without_exposing_raw_key() {
+        let private_key = EcPrivateKey::generate().expect("key generation should succeed");
+        let expected_public_key =
+            SignaturePublicKey::from(private_key.public_key().expect("public key derivation should succeed").to_bytes());
+        let signer = EcSigner::with_backend(
+            CipherSuite::P256_AES128,
+            Box::new(FixedBackend { private_key }),
+        )
+        .expect("cipher suite is supported");
+
+        // The stack still passes a `SignatureSecretKey`-shaped value through the
+        // `CipherSuiteProvider` trait, but the backend path never reads it.
+        let unused_secret_key = SignatureSecretKey::from(vec![0u8; 32]);
+
+        let public_key = signer
+            .signature_key_derive_public(&unused_secret_key)
+            .expect("backend public key lookup should succeed");
+        assert_eq!(public_key, expected_public_key);
+
+        let sig = signer
+            .sign(&unused_secret_key, b"hello world")
+            .expect("backend signing should succeed");
+        signer
+            .verify(&public_key, &sig, b"hello world")
+            .expect("verification should succeed");
+    }
+
+    #[test]
+    fn backend_rejects_key_generation() {
+        let private_key = EcPrivateKey::generate().expect("key generation should succeed");
+        let signer = EcSigner::with_backend(
+            CipherSuite::P256_AES128,
+            Box::new(FixedBackend { private_key }),
+        )
+        .expect("cipher suite is supported");
+        assert!(matches!(
+            signer.signature_key_generate(),
+            Err(EcSignerError::BackendKeyGenerationUnsupported)
+        ));
+    }
+}